@@ -0,0 +1,106 @@
+// Turns the bundled Unicode CLDR emoji data into static lookup tables,
+// see scripts/update_emoji_data.sh for where the files come from.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::{env, fs};
+
+const CLDR_DIR: &str = "data/cldr";
+const EMOJI_TEST: &str = "data/unicode/emoji-test.txt";
+// English must stay, it is the fallback and always searched
+const LOCALES: &[&str] = &["en", "pl", "de"];
+
+// CLDR keys carry no variation selectors, neither do the generated tables
+fn table_key(emoji: &str) -> String {
+    emoji.replace('\u{fe0f}', "")
+}
+
+// the data is committed, a missing file means a broken checkout
+fn read_data(path: &Path) -> String {
+    println!("cargo:rerun-if-changed={}", path.display());
+    fs::read_to_string(path).unwrap_or_else(|e| {
+        panic!("emoji data {} unavailable ({}), run scripts/update_emoji_data.sh", path.display(), e)
+    })
+}
+
+// emoji -> (name, keywords) from CLDR annotations and derived annotations (sequences, flags, skin tones)
+fn read_annotations(locale: &str) -> BTreeMap<String, (String, Vec<String>)> {
+    let mut annotations: BTreeMap<String, (String, Vec<String>)> = BTreeMap::new();
+    let dir = Path::new(CLDR_DIR).join(locale);
+
+    for (file, root) in [("annotations.json", "annotations"), ("annotationsDerived.json", "annotationsDerived")] {
+        let content = read_data(&dir.join(file));
+        let json: serde_json::Value = serde_json::from_str(&content)
+            .unwrap_or_else(|e| panic!("invalid CLDR file {}/{}: {}", locale, file, e));
+        let Some(entries) = json[root]["annotations"].as_object() else {
+            panic!("unexpected layout of CLDR file {}/{}", locale, file);
+        };
+
+        for (emoji, entry) in entries {
+            let (name, keywords) = annotations.entry(table_key(emoji)).or_default();
+            // tts holds the name, default the keywords
+            let words = |field: &str| entry[field].as_array().into_iter().flatten().filter_map(|w| w.as_str());
+            if let Some(tts) = words("tts").next() {
+                *name = tts.to_string();
+            }
+            for word in words("tts").chain(words("default")) {
+                let word = word.to_lowercase();
+                if !keywords.contains(&word) {
+                    keywords.push(word);
+                }
+            }
+        }
+    }
+    annotations
+}
+
+// emoji -> subgroup from emoji-test.txt, e.g. "face-smiling" becomes "face smiling"
+fn read_subgroups() -> BTreeMap<String, String> {
+    let mut subgroups = BTreeMap::new();
+    let content = read_data(Path::new(EMOJI_TEST));
+
+    let mut subgroup = String::new();
+    for line in content.lines() {
+        if let Some(name) = line.strip_prefix("# subgroup:") {
+            subgroup = name.trim().replace('-', " ");
+        } else if let Some((_, comment)) = line.split_once("; fully-qualified") {
+            // "# 😀 E1.0 grinning face"
+            if let Some(emoji) = comment.trim_start_matches([' ', '#']).split(' ').next() {
+                subgroups.insert(table_key(emoji), subgroup.clone());
+            }
+        }
+    }
+    subgroups
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+
+    let mut out = String::from("// @generated by build.rs\n\n");
+
+    // locale -> [(emoji, name, keywords)]
+    out.push_str("static ANNOTATIONS: &[(&str, &[Annotation])] = &[\n");
+    for locale in LOCALES {
+        writeln!(out, "    ({:?}, &[", locale).unwrap();
+        let annotations = read_annotations(locale);
+        // an empty table would silently leave the locale with English names
+        if annotations.values().all(|(name, _)| name.is_empty()) {
+            panic!("no CLDR emoji names for {}, run scripts/update_emoji_data.sh", locale);
+        }
+        for (emoji, (name, keywords)) in annotations {
+            writeln!(out, "        ({:?}, {:?}, &{:?}),", emoji, name, keywords).unwrap();
+        }
+        out.push_str("    ]),\n");
+    }
+    out.push_str("];\n\n");
+
+    out.push_str("static SUBGROUPS: &[(&str, &str)] = &[\n");
+    for (emoji, subgroup) in read_subgroups() {
+        writeln!(out, "    ({:?}, {:?}),", emoji, subgroup).unwrap();
+    }
+    out.push_str("];\n");
+
+    let out_path = PathBuf::from(env::var("OUT_DIR").unwrap()).join("emoji_annotations.rs");
+    fs::write(out_path, out).expect("failed to write emoji tables");
+}
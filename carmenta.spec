@@ -7,8 +7,8 @@ License:        MIT
 URL:            https://github.com/szymonwilczek/carmenta
 Source0:        %{url}/archive/refs/heads/main.tar.gz
 
-BuildRequires:  rust
-BuildRequires:  cargo
+BuildRequires:  rust >= 1.89
+BuildRequires:  cargo >= 1.89
 BuildRequires:  gtk4-devel
 BuildRequires:  libadwaita-devel
 BuildRequires:  desktop-file-utils
@@ -7,6 +7,10 @@ import GLib from "gi://GLib";
 import * as Main from "resource:///org/gnome/shell/ui/main.js";
 import { Extension } from "resource:///org/gnome/shell/extensions/extension.js";
 
+// bumped whenever the D-Bus interface changes, the app checks it at startup
+const PROTOCOL_VERSION = 2;
+const CAPABILITIES = ["insert-text", "pin-window"];
+
 const ServiceIface = `
 <node>
   <interface name="org.gnome.Shell.Extensions.Carmenta">
@@ -19,6 +23,12 @@ const ServiceIface = `
     <method name="PinWindow">
       <arg type="b" direction="in" name="pinned" />
     </method>
+    <method name="GetVersion">
+      <arg type="u" direction="out" name="version" />
+    </method>
+    <method name="GetCapabilities">
+      <arg type="as" direction="out" name="capabilities" />
+    </method>
   </interface>
 </node>`;
 
@@ -147,6 +157,14 @@ export default class CarmentaExtension extends Extension {
     return "Pong";
   }
 
+  GetVersion() {
+    return PROTOCOL_VERSION;
+  }
+
+  GetCapabilities() {
+    return CAPABILITIES;
+  }
+
   PinWindow(pinned) {
     // find carmenta window
     let carmentaWin = this._findCarmentaWindow();
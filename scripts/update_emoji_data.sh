@@ -0,0 +1,28 @@
+#!/bin/bash
+set -e
+
+# Emoji keyword data bundled into the binary by build.rs.
+# Bump the versions together with the `emojis` crate (0.8.2 is Emoji 17.0,
+# first annotated in CLDR 48). Commit the fetched files.
+CLDR_VERSION="48.0.0"
+EMOJI_VERSION="17.0"
+LOCALES="en pl de"
+
+CLDR_URL="https://raw.githubusercontent.com/unicode-org/cldr-json/$CLDR_VERSION/cldr-json"
+
+cd "$(dirname "$0")/.."
+
+echo "📥 Fetching Unicode emoji-test.txt ($EMOJI_VERSION)..."
+mkdir -p data/unicode
+curl -fsSL "https://unicode.org/Public/emoji/$EMOJI_VERSION/emoji-test.txt" -o data/unicode/emoji-test.txt
+
+for locale in $LOCALES; do
+    echo "📥 Fetching CLDR annotations for $locale ($CLDR_VERSION)..."
+    mkdir -p "data/cldr/$locale"
+    curl -fsSL "$CLDR_URL/cldr-annotations-full/annotations/$locale/annotations.json" \
+        -o "data/cldr/$locale/annotations.json"
+    curl -fsSL "$CLDR_URL/cldr-annotations-derived-full/annotationsDerived/$locale/annotations.json" \
+        -o "data/cldr/$locale/annotationsDerived.json"
+done
+
+echo "✅ Emoji data updated, rebuild Carmenta to pick it up"
@@ -1,13 +1,91 @@
 use gtk4::prelude::*;
-use gtk4::glib;
+use gtk4::{gio, glib};
 use libadwaita::Application;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use std::marker::PhantomData;
+use std::rc::Rc;
 use crate::window::CarmentaWindow;
 
 // Global state to track insertion
 thread_local! {
     pub static IS_INSERTING: RefCell<bool> = RefCell::new(false);
     static INSERT_TIMER: RefCell<Option<glib::SourceId>> = RefCell::new(None);
+    // file dialogs take focus, the window must not treat that as "focus lost"
+    pub static IS_DIALOG_OPEN: RefCell<bool> = const { RefCell::new(false) };
+    // --resident: keep running hidden instead of quitting, re-show on activation
+    static RESIDENT: Cell<bool> = const { Cell::new(false) };
+    static MAIN_WINDOW: RefCell<Option<Rc<CarmentaWindow>>> = const { RefCell::new(None) };
+    // live KeepAlive guards, and whether the picker was dismissed meanwhile
+    static KEEP_ALIVE: Cell<u32> = const { Cell::new(0) };
+    static QUIT_PENDING: Cell<bool> = const { Cell::new(false) };
+    // toasts raised before the window exists, e.g. while loading data files
+    static QUEUED_TOASTS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+pub fn is_resident() -> bool {
+    RESIDENT.with(|r| r.get())
+}
+
+/// Get the picker out of the way: hide it in resident mode, quit otherwise
+/// (once no KeepAlive is left)
+pub fn dismiss() {
+    if is_resident() {
+        hide_window();
+    } else if KEEP_ALIVE.with(|k| k.get()) > 0 {
+        hide_window();
+        QUIT_PENDING.with(|q| q.set(true));
+    } else if let Some(app) = gio::Application::default() {
+        app.quit();
+    }
+}
+
+/// Keeps a dismissed, non-resident picker running hidden until dropped, e.g.
+/// while the clipboard holds contents only this process can hand out
+pub struct KeepAlive {
+    // also outlives a closed window
+    _hold: Option<gio::ApplicationHoldGuard>,
+    _main_thread: PhantomData<Rc<()>>,
+}
+
+pub fn keep_alive() -> KeepAlive {
+    KEEP_ALIVE.with(|k| k.set(k.get() + 1));
+    KeepAlive {
+        _hold: gio::Application::default().map(|app| app.hold()),
+        _main_thread: PhantomData,
+    }
+}
+
+impl Drop for KeepAlive {
+    fn drop(&mut self) {
+        let left = KEEP_ALIVE.with(|k| {
+            k.set(k.get() - 1);
+            k.get()
+        });
+        if left == 0 && QUIT_PENDING.with(|q| q.replace(false)) {
+            dismiss();
+        }
+    }
+}
+
+/// Hide the picker without quitting, so keyboard focus goes back to the previous window
+pub fn hide_window() {
+    MAIN_WINDOW.with(|w| {
+        if let Some(window) = w.borrow().as_ref() {
+            window.window.set_visible(false);
+        }
+    });
+}
+
+/// Short notification inside the picker window, shown once it opens
+pub fn show_toast(message: &str) {
+    MAIN_WINDOW.with(|w| match w.borrow().as_ref() {
+        Some(window) => window.show_toast(message),
+        None => QUEUED_TOASTS.with(|q| q.borrow_mut().push(message.to_string())),
+    });
+}
+
+pub fn set_dialog_open(open: bool) {
+    IS_DIALOG_OPEN.with(|f| *f.borrow_mut() = open);
 }
 
 pub fn mark_inserting() {
@@ -26,6 +104,29 @@ pub fn mark_inserting() {
     });
 }
 
+/// Where the window opens, from `--page`, `--category` and `--query`
+#[derive(Default, Debug, Clone)]
+pub struct LaunchOptions {
+    pub page: Option<String>,
+    pub category: Option<String>,
+    pub query: Option<String>,
+}
+
+impl LaunchOptions {
+    fn from_dict(options: &glib::VariantDict) -> Self {
+        let lookup = |key: &str| options.lookup::<String>(key).ok().flatten();
+        Self {
+            page: lookup("page"),
+            category: lookup("category"),
+            query: lookup("query"),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.page.is_none() && self.category.is_none() && self.query.is_none()
+    }
+}
+
 pub struct CarmentaApp {
     app: Application,
 }
@@ -34,9 +135,54 @@ impl CarmentaApp {
     pub fn new(app_id: &str) -> Self {
         let app = Application::builder()
             .application_id(app_id)
+            .flags(gio::ApplicationFlags::HANDLES_COMMAND_LINE)
             .build();
 
+        app.add_main_option(
+            "incognito",
+            glib::Char::from(b'i'),
+            glib::OptionFlags::NONE,
+            glib::OptionArg::None,
+            "Don't record history in this session",
+            None,
+        );
+        app.add_main_option(
+            "resident",
+            glib::Char::from(b'r'),
+            glib::OptionFlags::NONE,
+            glib::OptionArg::None,
+            "Keep running hidden between uses, launching again toggles the window",
+            None,
+        );
+        app.add_main_option(
+            "page",
+            glib::Char::from(b'p'),
+            glib::OptionFlags::NONE,
+            glib::OptionArg::String,
+            "Page to open: all, emoji, kaomoji, symbols or gifs",
+            Some("PAGE"),
+        );
+        app.add_main_option(
+            "category",
+            glib::Char::from(b'c'),
+            glib::OptionFlags::NONE,
+            glib::OptionArg::String,
+            "Category to select, e.g. flags, recent, arrows",
+            Some("CATEGORY"),
+        );
+        app.add_main_option(
+            "query",
+            glib::Char::from(b'q'),
+            glib::OptionFlags::NONE,
+            glib::OptionArg::String,
+            "Prefill the search",
+            Some("QUERY"),
+        );
+
+        app.connect_command_line(Self::on_command_line);
         app.connect_activate(Self::on_activate);
+        // history saves are debounced, write what's pending before exiting
+        app.connect_shutdown(|_| crate::history::flush());
 
         Self { app }
     }
@@ -45,11 +191,54 @@ impl CarmentaApp {
         self.app.run();
     }
 
+    fn on_command_line(app: &Application, command_line: &gio::ApplicationCommandLine) -> i32 {
+        let options = command_line.options_dict();
+        if options.contains("incognito") {
+            // through the menu action once it exists, so its toggle follows
+            match app.lookup_action("incognito") {
+                Some(action) => action.change_state(&true.to_variant()),
+                None => crate::history::set_incognito(true),
+            }
+        }
+        if options.contains("resident") {
+            RESIDENT.with(|r| r.set(true));
+        }
+        Self::show(app, &LaunchOptions::from_dict(&options));
+        0
+    }
+
     fn on_activate(app: &Application) {
+        Self::show(app, &LaunchOptions::default());
+    }
+
+    fn show(app: &Application, options: &LaunchOptions) {
+        // shown again before a pending quit
+        QUIT_PENDING.with(|q| q.set(false));
         // prefetching DBus connection to avoid flicker on first insert
         crate::dbus::DBusClient::init_connection();
+        // pick up history written by other instances
+        crate::history::watch();
+
+        // re-use the window (and its populated stores) of a running instance
+        let existing = MAIN_WINDOW.with(|w| w.borrow().clone());
+        if let Some(window) = existing {
+            if is_resident() && window.window.is_visible() && options.is_empty() {
+                // launching again while shown acts as a toggle
+                window.window.set_visible(false);
+                return;
+            }
+            window.reset();
+            window.apply_launch_options(options);
+            window.present();
+            return;
+        }
         
-        let window = CarmentaWindow::new(app);
+        let window = Rc::new(CarmentaWindow::new(app));
+        window.apply_launch_options(options);
         window.present();
+        for message in QUEUED_TOASTS.with(|q| q.take()) {
+            window.show_toast(&message);
+        }
+        MAIN_WINDOW.with(|w| *w.borrow_mut() = Some(window));
     }
 }
@@ -0,0 +1,111 @@
+use serde::Serialize;
+use crate::history::ItemKind;
+use crate::search::Query;
+use crate::ui::emoji_data::get_all_emojis;
+use crate::ui::kaomoji_data::get_all_kaomojis;
+use crate::ui::symbols_data::get_symbols;
+
+const USAGE: &str = "\
+Usage:
+  carmenta                                   open the picker
+  carmenta search <query> [--kind emoji|kaomoji|symbol] [--json]
+  carmenta get <shortcode>                   print a single emoji, e.g. `carmenta get tada`";
+
+#[derive(Serialize)]
+struct SearchResult {
+    kind: ItemKind,
+    text: String,
+    name: String,
+}
+
+/// Headless subcommands, handled before any GTK setup.
+/// Returns None when the arguments are meant for the GUI.
+pub fn run(args: &[String]) -> Option<anyhow::Result<()>> {
+    match args.first().map(String::as_str) {
+        Some("search") => Some(search(&args[1..])),
+        Some("get") => Some(get(&args[1..])),
+        Some("help") => {
+            println!("{}", USAGE);
+            Some(Ok(()))
+        }
+        _ => None,
+    }
+}
+
+fn search(args: &[String]) -> anyhow::Result<()> {
+    let mut query = None;
+    let mut kinds = vec![ItemKind::Emoji, ItemKind::Kaomoji, ItemKind::Symbol];
+    let mut json = false;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--json" => json = true,
+            "--kind" => {
+                let kind = iter.next().map(String::as_str);
+                kinds = vec![match kind {
+                    Some("emoji") => ItemKind::Emoji,
+                    Some("kaomoji") => ItemKind::Kaomoji,
+                    Some("symbol") => ItemKind::Symbol,
+                    _ => anyhow::bail!("--kind expects emoji, kaomoji or symbol\n\n{}", USAGE),
+                }];
+            }
+            _ if query.is_none() => query = Some(arg.clone()),
+            _ => anyhow::bail!("unexpected argument '{}'\n\n{}", arg, USAGE),
+        }
+    }
+    let Some(query) = query else {
+        anyhow::bail!("missing search query\n\n{}", USAGE);
+    };
+
+    // (score, result), ranked the same way as in the picker
+    let mut results = Vec::new();
+    for kind in kinds {
+        let query = Query::new(&query, kind);
+        match kind {
+            ItemKind::Emoji => results.extend(
+                get_all_emojis().into_iter()
+                    .filter(|e| e.rank(&query))
+                    .map(|e| (e.search_score(), SearchResult { kind, text: e.emoji(), name: e.name() })),
+            ),
+            ItemKind::Kaomoji => results.extend(
+                get_all_kaomojis().into_iter()
+                    .filter(|k| k.rank(&query))
+                    .map(|k| (k.search_score(), SearchResult { kind, text: k.text(), name: k.name() })),
+            ),
+            ItemKind::Symbol => results.extend(
+                get_symbols().into_iter()
+                    .filter(|s| s.rank(&query))
+                    .map(|s| (s.search_score(), SearchResult { kind, text: s.char(), name: s.name() })),
+            ),
+            ItemKind::Gif => {} // needs network, GUI only
+        }
+    }
+    results.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    let results: Vec<SearchResult> = results.into_iter().map(|(_, result)| result).collect();
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&results)?);
+    } else {
+        for result in results {
+            println!("{}\t{}", result.text, result.name);
+        }
+    }
+    Ok(())
+}
+
+fn get(args: &[String]) -> anyhow::Result<()> {
+    let [shortcode] = args else {
+        anyhow::bail!("expected exactly one shortcode\n\n{}", USAGE);
+    };
+
+    // colons are optional here, `tada` works like `:tada:`
+    let full = format!(":{}:", shortcode.trim_matches(':'));
+    match crate::shortcode::resolve(&full) {
+        Some(emoji) => {
+            println!("{}", emoji.as_str());
+            Ok(())
+        }
+        None => anyhow::bail!("unknown shortcode '{}'", shortcode),
+    }
+}
@@ -1,7 +1,8 @@
 use zbus::Connection;
-use gtk4::gdk;
-use gtk4::prelude::*;
+use gtk4::glib;
 use tokio::sync::OnceCell;
+use std::cell::RefCell;
+use std::sync::Mutex;
 use std::time::Duration;
 
 pub struct DBusClient;
@@ -9,60 +10,214 @@ pub struct DBusClient;
 // global async-safe connection cache
 static CONNECTION: OnceCell<Connection> = OnceCell::const_new();
 
+// connection, extension & insertion state, written from the tokio side
+static STATE: Mutex<Diagnostics> = Mutex::new(Diagnostics {
+    connected: false,
+    extension: ExtensionStatus::Unknown,
+    last_backend: None,
+    last_error: None,
+});
+
+thread_local! {
+    // Callbacks to notify UI when the D-Bus state changes
+    static STATUS_CALLBACKS: RefCell<Vec<Box<dyn Fn()>>> = const { RefCell::new(Vec::new()) };
+}
+
 // timeout for DBus operations
 const DBUS_TIMEOUT: Duration = Duration::from_millis(500);
 
-impl DBusClient {
-    pub fn init_connection() {
-        if let Some(rt) = crate::RUNTIME.get() {
-            rt.spawn(async move {
-                let _ = Self::get_connection().await;
-            });
+const EXTENSION_NAME: &str = "org.gnome.Shell.Extensions.Carmenta";
+
+/// Extension protocol this build talks, extensions before the handshake are version 1
+pub const PROTOCOL_VERSION: u32 = 2;
+
+// what version 1 extensions can do, they can't be asked
+const V1_CAPABILITIES: &[&str] = &["insert-text", "pin-window"];
+
+#[zbus::proxy(
+    interface = "org.gnome.Shell.Extensions.Carmenta",
+    default_service = "org.gnome.Shell.Extensions.Carmenta",
+    default_path = "/org/gnome/Shell/Extensions/Carmenta"
+)]
+trait Carmenta {
+    fn ping(&self) -> zbus::Result<String>;
+
+    fn insert_text(&self, text: &str) -> zbus::Result<()>;
+
+    fn pin_window(&self, pinned: bool) -> zbus::Result<()>;
+
+    /// Protocol version, since version 2
+    fn get_version(&self) -> zbus::Result<u32>;
+
+    /// Features like "insert-text", since version 2
+    fn get_capabilities(&self) -> zbus::Result<Vec<String>>;
+}
+
+/// State of the GNOME Shell extension as seen by the startup handshake
+#[derive(Clone, Debug, PartialEq)]
+pub enum ExtensionStatus {
+    /// No handshake finished yet
+    Unknown,
+    /// Nobody owns the extension's bus name (not installed, disabled or not GNOME)
+    Missing,
+    /// Answers, but speaks an older protocol than this build
+    TooOld { version: u32 },
+    Working { version: u32, capabilities: Vec<String> },
+}
+
+impl ExtensionStatus {
+    /// Whether the extension (probably) supports `capability`, before the
+    /// handshake finishes we optimistically assume it does
+    pub fn has_capability(&self, capability: &str) -> bool {
+        match self {
+            ExtensionStatus::Unknown => true,
+            ExtensionStatus::Missing => false,
+            ExtensionStatus::TooOld { .. } => V1_CAPABILITIES.contains(&capability),
+            ExtensionStatus::Working { capabilities, .. } => capabilities.iter().any(|c| c == capability),
         }
     }
 
-    pub fn insert_or_copy(text: &str) {
-        let text_owned = text.to_string();
+    /// One line for the diagnostics page
+    pub fn summary(&self) -> String {
+        match self {
+            ExtensionStatus::Unknown => "Not checked yet".to_string(),
+            ExtensionStatus::Missing => "Not running".to_string(),
+            ExtensionStatus::TooOld { version } => format!("Outdated (protocol {})", version),
+            ExtensionStatus::Working { version, .. } => format!("Working (protocol {})", version),
+        }
+    }
+
+    /// Why insertion falls back to the clipboard, for the toast
+    pub fn fallback_reason(&self) -> &'static str {
+        match self {
+            ExtensionStatus::Missing => "extension not running",
+            ExtensionStatus::TooOld { .. } => "extension outdated",
+            _ => "extension cannot insert text",
+        }
+    }
+
+    /// Explanation for the user, None while everything is fine
+    pub fn problem(&self) -> Option<String> {
+        match self {
+            ExtensionStatus::Unknown | ExtensionStatus::Working { .. } => None,
+            ExtensionStatus::Missing => Some(
+                "The Carmenta GNOME Shell extension is not running, items are copied to the clipboard".to_string()
+            ),
+            ExtensionStatus::TooOld { version } => Some(format!(
+                "The Carmenta GNOME Shell extension is outdated (protocol {}, this app needs {}), please update it",
+                version, PROTOCOL_VERSION
+            )),
+        }
+    }
+}
+
+/// What the diagnostics page shows
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostics {
+    /// Session bus connection is up
+    pub connected: bool,
+    pub extension: ExtensionStatus,
+    /// Name of the insertion backend that handled the last item
+    pub last_backend: Option<&'static str>,
+    pub last_error: Option<String>,
+}
+
+pub fn diagnostics() -> Diagnostics {
+    STATE.lock().map(|s| s.clone()).unwrap_or_else(|e| e.into_inner().clone())
+}
+
+pub fn extension_status() -> ExtensionStatus {
+    diagnostics().extension
+}
+
+/// Register a callback (main thread) to be called when the extension status,
+/// the connection or the last error change
+pub fn on_status_changed<F: Fn() + 'static>(callback: F) {
+    STATUS_CALLBACKS.with(|callbacks| {
+        callbacks.borrow_mut().push(Box::new(callback));
+    });
+}
+
+fn update_state(change: impl FnOnce(&mut Diagnostics)) {
+    let changed = match STATE.lock() {
+        Ok(mut state) => {
+            let before = state.clone();
+            change(&mut state);
+            *state != before
+        }
+        Err(_) => false,
+    };
+    if changed {
+        glib::MainContext::default().invoke(|| {
+            STATUS_CALLBACKS.with(|callbacks| {
+                for callback in callbacks.borrow().iter() {
+                    callback();
+                }
+            });
+        });
+    }
+}
+
+fn set_extension_status(status: ExtensionStatus) {
+    update_state(|s| s.extension = status);
+}
+
+pub fn record_backend(name: &'static str) {
+    update_state(|s| s.last_backend = Some(name));
+}
+
+/// Log an error and keep it for the diagnostics page
+pub fn record_error(message: String) {
+    eprintln!("{}", message);
+    update_state(|s| s.last_error = Some(message));
+}
+
+impl DBusClient {
+    /// Connect to the session bus and find out what the extension can do
+    pub fn init_connection() {
         if let Some(rt) = crate::RUNTIME.get() {
             rt.spawn(async move {
-                // wrap the extension call with a timeout
-                let result = tokio::time::timeout(
-                    DBUS_TIMEOUT,
-                    Self::try_insert_via_extension(&text_owned)
-                ).await;
-                
-                match result {
-                    Ok(Ok(_)) => {}, // success
+                match tokio::time::timeout(DBUS_TIMEOUT, Self::handshake()).await {
+                    Ok(Ok(status)) => set_extension_status(status),
                     Ok(Err(e)) => {
-                        eprintln!("DBus error: {}", e);
-                        Self::fallback_copy_and_quit(text_owned);
+                        record_error(format!("DBus error: {}", e));
+                        set_extension_status(ExtensionStatus::Missing);
                     }
                     Err(_) => {
-                        eprintln!("DBus timeout: extension did not respond in {:?}", DBUS_TIMEOUT);
-                        Self::fallback_copy_and_quit(text_owned);
+                        record_error(format!("DBus timeout: extension handshake did not complete in {:?}", DBUS_TIMEOUT));
+                        set_extension_status(ExtensionStatus::Missing);
                     }
                 }
             });
-        } else {
-            eprintln!("Runtime not initialized!");
         }
     }
-    
-    fn fallback_copy_and_quit(text: String) {
-        gtk4::glib::MainContext::default().invoke(move || {
-            Self::copy_to_clipboard(&text);
-            gtk4::glib::timeout_add_local_once(
-                Duration::from_millis(100),
-                || {
-                    if let Some(app) = gtk4::gio::Application::default() {
-                        app.quit();
-                    }
-                }
-            );
-        });
+
+    async fn handshake() -> anyhow::Result<ExtensionStatus> {
+        let conn = Self::get_connection().await?;
+        let bus = zbus::fdo::DBusProxy::new(&conn).await?;
+        if !bus.name_has_owner(EXTENSION_NAME.try_into()?).await? {
+            return Ok(ExtensionStatus::Missing);
+        }
+
+        let proxy = CarmentaProxy::new(&conn).await?;
+        match proxy.get_version().await {
+            Ok(version) if version >= PROTOCOL_VERSION => Ok(ExtensionStatus::Working {
+                version,
+                capabilities: proxy.get_capabilities().await?,
+            }),
+            Ok(version) => Ok(ExtensionStatus::TooOld { version }),
+            // no GetVersion yet: a version 1 extension if it answers at all
+            Err(_) => {
+                proxy.ping().await?;
+                Ok(ExtensionStatus::TooOld { version: 1 })
+            }
+        }
     }
 
     pub fn pin_window(pinned: bool) {
+        if !extension_status().has_capability("pin-window") {
+            return;
+        }
         if let Some(rt) = crate::RUNTIME.get() {
             rt.spawn(async move {
                 // shorter timeout for pin_window as its non-critical
@@ -71,51 +226,45 @@ impl DBusClient {
                     Self::do_pin_window(pinned)
                 ).await;
                 
-                if let Err(_) = result {
-                    eprintln!("DBus timeout: pin_window did not complete");
+                match result {
+                    Ok(Ok(())) => {}
+                    Ok(Err(e)) => record_error(format!("DBus error: pin_window failed: {}", e)),
+                    Err(_) => record_error("DBus timeout: pin_window did not complete".to_string()),
                 }
             });
         }
     }
     
     async fn do_pin_window(pinned: bool) -> anyhow::Result<()> {
-        let conn = Self::get_connection().await?;
-        conn.call_method(
-            Some("org.gnome.Shell.Extensions.Carmenta"), 
-            "/org/gnome/Shell/Extensions/Carmenta",    
-            Some("org.gnome.Shell.Extensions.Carmenta"), 
-            "PinWindow",
-            &(pinned),
-        ).await?;
+        Self::extension().await?.pin_window(pinned).await?;
         Ok(())
     }
 
-    async fn get_connection() -> anyhow::Result<Connection> {
-        let conn: &Connection = CONNECTION.get_or_try_init(|| async {
+    /// Session bus connection shared by everything talking D-Bus
+    pub async fn get_connection() -> anyhow::Result<Connection> {
+        let conn = CONNECTION.get_or_try_init(|| async {
             Connection::session().await
-        }).await?;
-        
-        Ok(conn.clone())
+        }).await;
+
+        match conn {
+            Ok(conn) => {
+                update_state(|s| s.connected = true);
+                Ok(conn.clone())
+            }
+            Err(e) => {
+                update_state(|s| s.connected = false);
+                Err(e.into())
+            }
+        }
     }
 
-    async fn try_insert_via_extension(text: &str) -> anyhow::Result<()> {
-        let connection = Self::get_connection().await?;
-        
-        connection.call_method(
-            Some("org.gnome.Shell.Extensions.Carmenta"), 
-            "/org/gnome/Shell/Extensions/Carmenta",    
-            Some("org.gnome.Shell.Extensions.Carmenta"), 
-            "InsertText",
-            &(text),
-        ).await?;
-        
-        Ok(())
+    async fn extension() -> anyhow::Result<CarmentaProxy<'static>> {
+        let conn = Self::get_connection().await?;
+        Ok(CarmentaProxy::new(&conn).await?)
     }
 
-    fn copy_to_clipboard(text: &str) {
-        let display = gdk::Display::default().expect("No display");
-        let clipboard = display.clipboard();
-        clipboard.set_text(text);
-        println!("Copied to clipboard: {}", text);
+    pub async fn insert_via_extension(text: &str) -> anyhow::Result<()> {
+        Self::extension().await?.insert_text(text).await?;
+        Ok(())
     }
 }
@@ -0,0 +1,88 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::cell::RefCell;
+use crate::storage;
+
+const FAVORITES_FILE: &str = "favorites.json";
+
+/// User-curated items, never trimmed like history
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Favorites {
+    pub items: Vec<String>,
+}
+
+impl Favorites {
+    fn get_path() -> PathBuf {
+        storage::data_file(FAVORITES_FILE)
+    }
+
+    pub fn load() -> Self {
+        storage::load_json(&Self::get_path()).unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        let path = Self::get_path();
+        match serde_json::to_vec(self) {
+            Ok(json) => {
+                if let Err(e) = storage::write_atomic(&path, &json) {
+                    eprintln!("Failed to save favorites: {}", e);
+                }
+            }
+            Err(e) => eprintln!("Failed to serialize favorites: {}", e),
+        }
+    }
+
+    pub fn contains(&self, text: &str) -> bool {
+        self.items.iter().any(|x| x == text)
+    }
+
+    /// Add or remove `text`, returns whether it is a favorite afterwards
+    pub fn toggle(&mut self, text: String) -> bool {
+        let added = if let Some(pos) = self.items.iter().position(|x| *x == text) {
+            self.items.remove(pos);
+            false
+        } else {
+            self.items.push(text);
+            true
+        };
+        self.save();
+        added
+    }
+}
+
+// Global favorites instance
+thread_local! {
+    static GLOBAL_FAVORITES: RefCell<Favorites> = RefCell::new(Favorites::load());
+    // Callbacks to notify UI when favorites change
+    static FAVORITES_CALLBACKS: RefCell<Vec<Box<dyn Fn()>>> = RefCell::new(Vec::new());
+}
+
+pub fn is_favorite(text: &str) -> bool {
+    GLOBAL_FAVORITES.with(|f| f.borrow().contains(text))
+}
+
+pub fn get_favorites() -> Vec<String> {
+    GLOBAL_FAVORITES.with(|f| f.borrow().items.clone())
+}
+
+pub fn toggle_favorite(text: String) {
+    GLOBAL_FAVORITES.with(|f| {
+        f.borrow_mut().toggle(text);
+    });
+    notify_favorites_changed();
+}
+
+/// Register a callback to be called when favorites change
+pub fn on_favorites_changed<F: Fn() + 'static>(callback: F) {
+    FAVORITES_CALLBACKS.with(|callbacks| {
+        callbacks.borrow_mut().push(Box::new(callback));
+    });
+}
+
+fn notify_favorites_changed() {
+    FAVORITES_CALLBACKS.with(|callbacks| {
+        for callback in callbacks.borrow().iter() {
+            callback();
+        }
+    });
+}
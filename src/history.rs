@@ -1,61 +1,313 @@
-use serde::{Deserialize, Serialize};
-use std::fs;
-use std::path::PathBuf;
-use std::cell::RefCell;
-use gtk4::glib;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::path::{Path, PathBuf};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use gtk4::{gio, glib};
+use gtk4::prelude::*;
+use crate::storage;
 
 const MAX_HISTORY: usize = 50;
 const HISTORY_FILE: &str = "history.json";
+// coalesce bursts of clicks into a single write
+const SAVE_DELAY: Duration = Duration::from_millis(500);
+// an entry's weight halves for every 3 days it goes unused
+const FRECENCY_HALF_LIFE_SECS: f64 = 3.0 * 24.0 * 60.0 * 60.0;
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Which page an item belongs to, each kind keeps its own history slice
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ItemKind {
+    Emoji,
+    Kaomoji,
+    Symbol,
+    Gif,
+}
+
+impl ItemKind {
+    // best guess for entries saved before history was typed
+    fn guess(text: &str) -> Self {
+        if emojis::get(text).is_some() {
+            ItemKind::Emoji
+        } else if text.starts_with("http://") || text.starts_with("https://") {
+            ItemKind::Gif
+        } else if text.chars().count() == 1 {
+            ItemKind::Symbol
+        } else {
+            ItemKind::Kaomoji
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct HistoryEntry {
+    pub kind: ItemKind,
+    pub text: String,
+    pub count: u32,
+    pub last_used: u64, // unix seconds
+    // small animation shown for GIFs, `text` is the full-size URL
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preview: Option<String>,
+}
+
+impl HistoryEntry {
+    fn new(kind: ItemKind, text: String, now: u64) -> Self {
+        Self { kind, text, count: 1, last_used: now, preview: None }
+    }
+
+    /// Use count decayed by time since last use
+    pub fn frecency(&self, now: u64) -> f64 {
+        let age = now.saturating_sub(self.last_used) as f64;
+        self.count as f64 * 0.5_f64.powf(age / FRECENCY_HALF_LIFE_SECS)
+    }
+}
+
+// history.json written before frecency stored plain strings, most recent first;
+// entries written before history was typed have no kind
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredEntry {
+    Legacy(String),
+    Entry {
+        kind: Option<ItemKind>,
+        text: String,
+        count: u32,
+        last_used: u64,
+        preview: Option<String>,
+    },
+}
+
+fn deserialize_entries<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<HistoryEntry>, D::Error> {
+    let stored = Vec::<StoredEntry>::deserialize(deserializer)?;
+    let now = unix_now();
+    Ok(stored
+        .into_iter()
+        .enumerate()
+        .map(|(i, entry)| match entry {
+            StoredEntry::Entry { kind, text, count, last_used, preview } => HistoryEntry {
+                kind: kind.unwrap_or_else(|| ItemKind::guess(&text)),
+                text,
+                count,
+                last_used,
+                preview,
+            },
+            // space legacy entries one second apart to keep their MRU order
+            StoredEntry::Legacy(text) => {
+                HistoryEntry::new(ItemKind::guess(&text), text, now.saturating_sub(i as u64))
+            }
+        })
+        .collect())
+}
+
+/// Variant last picked from an emoji's menu, shown instead of the base emoji
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct VariantChoice {
+    pub variant: String,
+    pub chosen_at: u64, // unix seconds
+}
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct History {
-    pub recent: Vec<String>,
+    #[serde(deserialize_with = "deserialize_entries")]
+    pub recent: Vec<HistoryEntry>,
+    // entries used before this (unix seconds) were cleared, also in other instances
+    #[serde(default)]
+    pub cleared_at: u64,
+    // keyed by base emoji
+    #[serde(default)]
+    pub variants: HashMap<String, VariantChoice>,
 }
 
 impl History {
     pub fn new() -> Self {
-        Self { recent: Vec::new() }
+        Self { recent: Vec::new(), cleared_at: 0, variants: HashMap::new() }
     }
 
     fn get_path() -> PathBuf {
-        let mut path = glib::user_data_dir(); // ~/.local/share
-        path.push("carmenta");
-        std::fs::create_dir_all(&path).ok();
-        path.push(HISTORY_FILE);
-        path
+        storage::data_file(HISTORY_FILE)
     }
 
     pub fn load() -> Self {
         let path = Self::get_path();
-        if let Ok(content) = fs::read_to_string(&path) {
-            if let Ok(history) = serde_json::from_str(&content) {
-                return history;
+        let _lock = storage::lock(&path, false)
+            .map_err(|e| eprintln!("Failed to lock history: {}", e))
+            .ok();
+        storage::load_json(&path).unwrap_or_else(Self::new)
+    }
+
+    /// Merge what other instances wrote since we loaded, then save.
+    /// Returns whether the merge changed our entries.
+    pub fn save(&mut self) -> bool {
+        let path = Self::get_path();
+        let _lock = match storage::lock(&path, true) {
+            Ok(lock) => lock,
+            Err(e) => {
+                eprintln!("Failed to lock history: {}", e);
+                return false;
             }
-        }
-        Self::new()
+        };
+
+        let changed = match storage::read_json::<History>(&path) {
+            Ok(Some(on_disk)) => self.merge(on_disk),
+            _ => false,
+        };
+
+        self.write(&path);
+        changed
     }
 
-    pub fn save(&self) {
+    /// Replace history.json with exactly this state, ignoring what's on disk
+    pub fn overwrite(&self) {
         let path = Self::get_path();
-        if let Ok(json) = serde_json::to_string(self) {
-            if let Err(e) = fs::write(&path, json) {
-                eprintln!("Failed to save history: {}", e);
+        match storage::lock(&path, true) {
+            Ok(_lock) => self.write(&path),
+            Err(e) => eprintln!("Failed to lock history: {}", e),
+        }
+    }
+
+    fn write(&self, path: &Path) {
+        match serde_json::to_vec(self) {
+            Ok(json) => {
+                if let Err(e) = storage::write_atomic(path, &json) {
+                    eprintln!("Failed to save history: {}", e);
+                }
+            }
+            Err(e) => eprintln!("Failed to serialize history: {}", e),
+        }
+    }
+
+    /// Sanity checks for history coming from outside (imports)
+    pub fn validate(&self) -> anyhow::Result<()> {
+        for (i, entry) in self.recent.iter().enumerate() {
+            if entry.text.is_empty() {
+                anyhow::bail!("entry {} has empty text", i);
+            }
+            if entry.count == 0 {
+                anyhow::bail!("entry {} ({}) has a zero use count", i, entry.text);
+            }
+            if self.recent[..i].iter().any(|e| e.kind == entry.kind && e.text == entry.text) {
+                anyhow::bail!("entry {} ({}) is listed twice", i, entry.text);
             }
         }
+        for (base, choice) in &self.variants {
+            if base.is_empty() || choice.variant.is_empty() {
+                anyhow::bail!("variant choice for '{}' is empty", base);
+            }
+        }
+        Ok(())
     }
 
-    pub fn add(&mut self, emoji: String) {
-        // Remove existing to bubble to top
-        if let Some(pos) = self.recent.iter().position(|x| *x == emoji) {
-            self.recent.remove(pos);
+    /// Fold in entries from another copy of the history, keeping the higher
+    /// count and the later use of each item, so merging our own data is a no-op.
+    /// Returns whether anything changed.
+    pub fn merge(&mut self, other: History) -> bool {
+        let mut changed = false;
+
+        // a clear in either copy wins over everything used before it
+        if other.cleared_at > self.cleared_at {
+            self.cleared_at = other.cleared_at;
+            let before = self.recent.len();
+            let cleared_at = self.cleared_at;
+            self.recent.retain(|e| e.last_used > cleared_at);
+            let variants_before = self.variants.len();
+            self.variants.retain(|_, c| c.chosen_at > cleared_at);
+            changed = self.recent.len() != before || self.variants.len() != variants_before;
+        }
+
+        // the later pick of each emoji's variant wins
+        for (base, theirs) in other.variants.into_iter().filter(|(_, c)| c.chosen_at > self.cleared_at) {
+            if self.variants.get(&base).is_none_or(|ours| theirs.chosen_at > ours.chosen_at) {
+                self.variants.insert(base, theirs);
+                changed = true;
+            }
+        }
+
+        let mut kinds = Vec::new();
+        for theirs in other.recent.into_iter().filter(|e| e.last_used > self.cleared_at) {
+            if !kinds.contains(&theirs.kind) {
+                kinds.push(theirs.kind);
+            }
+            match self.recent.iter_mut().find(|e| e.kind == theirs.kind && e.text == theirs.text) {
+                Some(ours) => {
+                    if theirs.count > ours.count || theirs.last_used > ours.last_used {
+                        ours.count = ours.count.max(theirs.count);
+                        ours.last_used = ours.last_used.max(theirs.last_used);
+                        changed = true;
+                    }
+                    if ours.preview.is_none() && theirs.preview.is_some() {
+                        ours.preview = theirs.preview;
+                        changed = true;
+                    }
+                }
+                None => {
+                    self.recent.push(theirs);
+                    changed = true;
+                }
+            }
         }
-        // Insert at beginning
-        self.recent.insert(0, emoji);
-        // Trim
-        if self.recent.len() > MAX_HISTORY {
-            self.recent.truncate(MAX_HISTORY);
+        for kind in kinds {
+            self.trim(kind);
+        }
+        changed
+    }
+
+    pub fn add(&mut self, kind: ItemKind, text: String) {
+        self.add_with_preview(kind, text, None);
+    }
+
+    fn add_with_preview(&mut self, kind: ItemKind, text: String, preview: Option<String>) {
+        let now = unix_now();
+        // Bump existing entry or start a new one
+        if let Some(entry) = self.recent.iter_mut().find(|e| e.kind == kind && e.text == text) {
+            entry.count = entry.count.saturating_add(1);
+            entry.last_used = now;
+            if preview.is_some() {
+                entry.preview = preview;
+            }
+        } else {
+            self.recent.push(HistoryEntry { preview, ..HistoryEntry::new(kind, text, now) });
+        }
+        self.trim(kind);
+    }
+
+    // Trim one kind's slice: evict the lowest scored entries, not the oldest ones
+    fn trim(&mut self, kind: ItemKind) {
+        let now = unix_now();
+        while self.recent.iter().filter(|e| e.kind == kind).count() > MAX_HISTORY {
+            let lowest = self.recent.iter()
+                .enumerate()
+                .filter(|(_, e)| e.kind == kind)
+                .min_by(|(_, a), (_, b)| a.frecency(now).total_cmp(&b.frecency(now)))
+                .map(|(i, _)| i);
+            match lowest {
+                Some(i) => self.recent.remove(i),
+                None => break,
+            };
         }
-        self.save();
+    }
+
+    /// Texts of one kind's entries, best frecency first
+    pub fn ranked(&self, kind: ItemKind) -> Vec<String> {
+        let now = unix_now();
+        let mut entries: Vec<&HistoryEntry> = self.recent.iter().filter(|e| e.kind == kind).collect();
+        entries.sort_by(|a, b| b.frecency(now).total_cmp(&a.frecency(now)));
+        entries.into_iter().map(|e| e.text.clone()).collect()
+    }
+
+    /// Frecency of every entry of one kind, keyed by text
+    pub fn frecencies(&self, kind: ItemKind) -> HashMap<String, f64> {
+        let now = unix_now();
+        self.recent.iter()
+            .filter(|e| e.kind == kind)
+            .map(|e| (e.text.clone(), e.frecency(now)))
+            .collect()
     }
 }
 
@@ -64,19 +316,200 @@ thread_local! {
     pub static GLOBAL_HISTORY: RefCell<History> = RefCell::new(History::load());
     // Callbacks to notify UI when history changes
     static HISTORY_CALLBACKS: RefCell<Vec<Box<dyn Fn()>>> = RefCell::new(Vec::new());
+    // Pending debounced save
+    static SAVE_TIMER: RefCell<Option<glib::SourceId>> = const { RefCell::new(None) };
+    // While set, nothing gets recorded and Recent views are hidden
+    static INCOGNITO: Cell<bool> = const { Cell::new(false) };
+    // Watches history.json for writes from other instances
+    static HISTORY_MONITOR: RefCell<Option<gio::FileMonitor>> = const { RefCell::new(None) };
 }
 
-pub fn add_recent(emoji: String) {
+pub fn add_recent(kind: ItemKind, text: String) {
+    if is_incognito() {
+        return;
+    }
     GLOBAL_HISTORY.with(|h| {
-        h.borrow_mut().add(emoji);
+        h.borrow_mut().add(kind, text);
     });
+    schedule_save();
     // Notify all registered callbacks
     notify_history_changed();
 }
 
-pub fn get_recent() -> Vec<String> {
+/// Record a used GIF along with its preview animation
+pub fn add_recent_gif(url: String, preview_url: String) {
+    if is_incognito() {
+        return;
+    }
+    GLOBAL_HISTORY.with(|h| {
+        h.borrow_mut().add_with_preview(ItemKind::Gif, url, Some(preview_url));
+    });
+    schedule_save();
+    notify_history_changed();
+}
+
+pub fn is_incognito() -> bool {
+    INCOGNITO.with(|i| i.get())
+}
+
+/// Suspend or resume history recording, history listeners are notified
+pub fn set_incognito(incognito: bool) {
+    if INCOGNITO.with(|i| i.replace(incognito)) != incognito {
+        notify_history_changed();
+    }
+}
+
+/// Save history once no more changes arrive for `SAVE_DELAY`
+fn schedule_save() {
+    SAVE_TIMER.with(|t| {
+        if let Some(source) = t.borrow_mut().take() {
+            source.remove();
+        }
+        let source = glib::timeout_add_local_once(SAVE_DELAY, || {
+            SAVE_TIMER.with(|t| *t.borrow_mut() = None);
+            if GLOBAL_HISTORY.with(|h| h.borrow_mut().save()) {
+                notify_history_changed();
+            }
+        });
+        *t.borrow_mut() = Some(source);
+    });
+}
+
+/// Write pending changes right away (on shutdown)
+pub fn flush() {
+    let pending = SAVE_TIMER.with(|t| t.borrow_mut().take());
+    if let Some(source) = pending {
+        source.remove();
+        GLOBAL_HISTORY.with(|h| h.borrow_mut().save());
+    }
+}
+
+/// Merge history written by other Carmenta instances as soon as it lands on disk
+pub fn watch() {
+    if HISTORY_MONITOR.with(|m| m.borrow().is_some()) {
+        return;
+    }
+
+    let file = gio::File::for_path(History::get_path());
+    let monitor = match file.monitor_file(gio::FileMonitorFlags::WATCH_MOVES, gio::Cancellable::NONE) {
+        Ok(monitor) => monitor,
+        Err(e) => {
+            eprintln!("Failed to watch history file: {}", e);
+            return;
+        }
+    };
+
+    monitor.connect_changed(|_, _, _, event| {
+        // saves replace the file via rename, plain writes end with ChangesDoneHint
+        if matches!(
+            event,
+            gio::FileMonitorEvent::ChangesDoneHint
+                | gio::FileMonitorEvent::Created
+                | gio::FileMonitorEvent::Renamed
+                | gio::FileMonitorEvent::MovedIn
+        ) {
+            reload_external();
+        }
+    });
+    HISTORY_MONITOR.with(|m| *m.borrow_mut() = Some(monitor));
+}
+
+fn reload_external() {
+    let path = History::get_path();
+    let on_disk = {
+        let _lock = storage::lock(&path, false).ok();
+        storage::read_json::<History>(&path)
+    };
+    // half-written or missing files are picked up on the next event
+    let Ok(Some(on_disk)) = on_disk else { return };
+
+    if GLOBAL_HISTORY.with(|h| h.borrow_mut().merge(on_disk)) {
+        notify_history_changed();
+    }
+}
+
+/// Forget all history, other running instances drop it on their next merge
+pub fn clear() {
+    GLOBAL_HISTORY.with(|h| {
+        let mut h = h.borrow_mut();
+        h.recent.clear();
+        h.variants.clear();
+        h.cleared_at = unix_now();
+        h.overwrite();
+    });
+    notify_history_changed();
+}
+
+/// Current history as pretty-printed JSON
+pub fn export_json() -> anyhow::Result<Vec<u8>> {
+    GLOBAL_HISTORY.with(|h| Ok(serde_json::to_vec_pretty(&*h.borrow())?))
+}
+
+/// Validate exported history and make it the current state
+pub fn import_json(json: &[u8]) -> anyhow::Result<()> {
+    let mut imported: History = serde_json::from_slice(json)?;
+    imported.validate()?;
+    for kind in [ItemKind::Emoji, ItemKind::Kaomoji, ItemKind::Symbol, ItemKind::Gif] {
+        imported.trim(kind);
+    }
+
+    GLOBAL_HISTORY.with(|h| {
+        imported.overwrite();
+        *h.borrow_mut() = imported;
+    });
+    notify_history_changed();
+    Ok(())
+}
+
+/// Recent items of one kind ranked by frecency
+pub fn get_recent(kind: ItemKind) -> Vec<String> {
+    GLOBAL_HISTORY.with(|h| {
+        h.borrow().ranked(kind)
+    })
+}
+
+/// Recent GIFs ranked by frecency, (full URL, preview URL if known)
+pub fn get_recent_gifs() -> Vec<(String, Option<String>)> {
+    GLOBAL_HISTORY.with(|h| {
+        let h = h.borrow();
+        h.ranked(ItemKind::Gif)
+            .into_iter()
+            .map(|url| {
+                let preview = h.recent.iter()
+                    .find(|e| e.kind == ItemKind::Gif && e.text == url)
+                    .and_then(|e| e.preview.clone());
+                (url, preview)
+            })
+            .collect()
+    })
+}
+
+/// Remember the variant picked from `base`'s menu
+pub fn remember_variant(base: &str, variant: &str) {
+    if is_incognito() {
+        return;
+    }
+    GLOBAL_HISTORY.with(|h| {
+        h.borrow_mut().variants.insert(base.to_string(), VariantChoice {
+            variant: variant.to_string(),
+            chosen_at: unix_now(),
+        });
+    });
+    schedule_save();
+    notify_history_changed();
+}
+
+/// Variant last picked for `base`, if any
+pub fn get_variant(base: &str) -> Option<String> {
     GLOBAL_HISTORY.with(|h| {
-        h.borrow().recent.clone()
+        h.borrow().variants.get(base).map(|c| c.variant.clone())
+    })
+}
+
+/// Frecency of recent items of one kind, used to boost search results
+pub fn get_frecencies(kind: ItemKind) -> HashMap<String, f64> {
+    GLOBAL_HISTORY.with(|h| {
+        h.borrow().frecencies(kind)
     })
 }
 
@@ -95,3 +528,108 @@ fn notify_history_changed() {
         }
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(json: &str) -> History {
+        serde_json::from_str(json).expect("history should parse")
+    }
+
+    #[test]
+    fn legacy_string_list_keeps_items_and_order() {
+        let history = parse(r#"{"recent": ["😀", "(◕‿◕)", "→", "https://example.com/a.gif"]}"#);
+        let kinds: Vec<ItemKind> = history.recent.iter().map(|e| e.kind).collect();
+        assert_eq!(kinds, [ItemKind::Emoji, ItemKind::Kaomoji, ItemKind::Symbol, ItemKind::Gif]);
+        assert!(history.recent.iter().all(|e| e.count == 1));
+        // most recent first, as the list was
+        assert!(history.recent.windows(2).all(|w| w[0].last_used > w[1].last_used));
+    }
+
+    #[test]
+    fn untyped_entries_keep_counts_and_get_a_kind() {
+        let history = parse(r#"{"recent": [
+            {"text": "😀", "count": 3, "last_used": 100},
+            {"text": "(◕‿◕)", "count": 2, "last_used": 90}
+        ]}"#);
+        assert_eq!(history.recent, [
+            HistoryEntry { kind: ItemKind::Emoji, text: "😀".into(), count: 3, last_used: 100, preview: None },
+            HistoryEntry { kind: ItemKind::Kaomoji, text: "(◕‿◕)".into(), count: 2, last_used: 90, preview: None },
+        ]);
+    }
+
+    #[test]
+    fn mixed_entries_migrate_and_survive_a_save() {
+        let history = parse(r#"{"recent": [
+            "😀",
+            {"kind": "symbol", "text": "★", "count": 2, "last_used": 50},
+            {"text": "→", "count": 4, "last_used": 40},
+            {"kind": "gif", "text": "https://example.com/a.gif", "count": 1, "last_used": 30, "preview": "https://example.com/a-small.gif"}
+        ]}"#);
+        let kinds: Vec<ItemKind> = history.recent.iter().map(|e| e.kind).collect();
+        assert_eq!(kinds, [ItemKind::Emoji, ItemKind::Symbol, ItemKind::Symbol, ItemKind::Gif]);
+        assert_eq!(history.recent[2].count, 4);
+        assert_eq!(history.recent[3].preview.as_deref(), Some("https://example.com/a-small.gif"));
+
+        let saved = serde_json::to_string(&history).unwrap();
+        assert_eq!(parse(&saved).recent, history.recent);
+    }
+
+    fn entry(kind: ItemKind, text: &str, count: u32, last_used: u64) -> HistoryEntry {
+        HistoryEntry { kind, text: text.into(), count, last_used, preview: None }
+    }
+
+    fn history(recent: Vec<HistoryEntry>) -> History {
+        History { recent, ..History::new() }
+    }
+
+    #[test]
+    fn merge_keeps_the_higher_count_and_later_use() {
+        let mut ours = history(vec![entry(ItemKind::Emoji, "😀", 5, 100)]);
+        let theirs = history(vec![entry(ItemKind::Emoji, "😀", 3, 200), entry(ItemKind::Symbol, "→", 1, 150)]);
+        assert!(ours.merge(theirs.clone()));
+        assert_eq!(ours.recent, [entry(ItemKind::Emoji, "😀", 5, 200), entry(ItemKind::Symbol, "→", 1, 150)]);
+        // nothing new the second time
+        assert!(!ours.merge(theirs));
+        assert!(!ours.merge(ours.clone()));
+    }
+
+    #[test]
+    fn merge_after_clear_drops_older_entries() {
+        let stale = History {
+            variants: HashMap::from([("👋".to_string(), VariantChoice { variant: "👋🏽".into(), chosen_at: 100 })]),
+            ..history(vec![entry(ItemKind::Emoji, "😀", 4, 100), entry(ItemKind::Emoji, "🎉", 1, 300)])
+        };
+
+        // the other instance cleared at 200 and used 🎉 afterwards
+        let mut cleared = History { cleared_at: 200, ..History::new() };
+        cleared.merge(stale.clone());
+        assert_eq!(cleared.recent, [entry(ItemKind::Emoji, "🎉", 1, 300)]);
+        assert!(cleared.variants.is_empty());
+
+        // and the stale instance learns about the clear
+        let mut ours = stale;
+        assert!(ours.merge(cleared));
+        assert_eq!(ours.cleared_at, 200);
+        assert_eq!(ours.recent, [entry(ItemKind::Emoji, "🎉", 1, 300)]);
+        assert!(ours.variants.is_empty());
+    }
+
+    #[test]
+    fn merge_of_the_same_item_used_in_both_instances() {
+        let base = history(vec![entry(ItemKind::Emoji, "😀", 1, 100)]);
+        let mut a = base.clone();
+        let mut b = base;
+        a.add(ItemKind::Emoji, "😀".into());
+        b.add(ItemKind::Emoji, "😀".into());
+        b.add(ItemKind::Kaomoji, "(◕‿◕)".into());
+
+        a.merge(b);
+        let smiles: Vec<&HistoryEntry> = a.recent.iter().filter(|e| e.text == "😀").collect();
+        assert_eq!(smiles.len(), 1);
+        assert_eq!(smiles[0].count, 2);
+        assert!(smiles[0].last_used > 100);
+        assert_eq!(a.ranked(ItemKind::Kaomoji), ["(◕‿◕)"]);
+    }
+}
@@ -0,0 +1,247 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tokio::sync::Mutex;
+use gtk4::{gdk, gio, glib};
+use gtk4::prelude::*;
+use super::{on_main_thread, BoxFuture, InsertionBackend};
+
+// long enough to read the "copied to clipboard" toast
+const DISMISS_DELAY: Duration = Duration::from_millis(1500);
+// time for the target app to read a pasted item before the clipboard is restored
+const DEFAULT_RESTORE_DELAY: Duration = Duration::from_millis(500);
+// larger clipboards (huge images) are left alone rather than kept in memory
+const MAX_SNAPSHOT_SIZE: usize = 32 * 1024 * 1024;
+// an owner that doesn't answer shouldn't hold up the insertion
+const SNAPSHOT_TIMEOUT: Duration = Duration::from_secs(1);
+
+// snapshot waiting to be restored & the paste it belongs to. Pastes in quick
+// succession share it, otherwise the second would snapshot the first item.
+static PENDING: Mutex<Option<(u64, ClipboardSnapshot)>> = Mutex::const_new(None);
+static PASTE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+thread_local! {
+    // keeps the process alive for the pending or restored clipboard contents
+    static HOLD: RefCell<Option<crate::app::KeepAlive>> = const { RefCell::new(None) };
+}
+
+/// Menu choices for `Settings::clipboard_restore_delay`
+pub const RESTORE_DELAYS: &[(&str, &str, Option<u64>)] = &[
+    ("default", "After Half a Second", None),
+    ("250", "After a Quarter Second", Some(250)),
+    ("1000", "After a Second", Some(1000)),
+    ("3000", "After Three Seconds", Some(3000)),
+    ("never", "Never", Some(0)),
+];
+
+/// Leaves the item on the clipboard for the user to paste, works everywhere.
+/// The item is what the user came for, so the previous contents aren't restored.
+pub struct ClipboardBackend;
+
+impl ClipboardBackend {
+    pub const ID: &'static str = "clipboard";
+
+    /// Copy `text` (on the main thread), show `message` and get out of the way
+    pub fn copy_and_dismiss(text: String, message: String) {
+        glib::MainContext::default().invoke(move || {
+            copy_to_clipboard(&text);
+            crate::app::show_toast(&message);
+            glib::timeout_add_local_once(DISMISS_DELAY, crate::app::dismiss);
+        });
+    }
+}
+
+/// How long after pasting the previous clipboard comes back, None when it doesn't
+pub async fn restore_delay() -> Option<Duration> {
+    let delay = on_main_thread(|| async { Ok(crate::settings::get().clipboard_restore_delay) }).await.ok()?;
+    match delay {
+        None => Some(DEFAULT_RESTORE_DELAY),
+        Some(0) => None,
+        Some(ms) => Some(Duration::from_millis(ms)),
+    }
+}
+
+// The restored contents are offered by this process, so a dismissed picker
+// stays (hidden) from the snapshot until another app owns the clipboard
+fn hold() {
+    HOLD.with(|hold| {
+        hold.borrow_mut().get_or_insert_with(crate::app::keep_alive);
+    });
+}
+
+fn release() {
+    HOLD.with(|hold| hold.borrow_mut().take());
+}
+
+fn release_when_replaced(clipboard: &gdk::Clipboard) {
+    let handler: Rc<RefCell<Option<glib::SignalHandlerId>>> = Rc::default();
+    let id = clipboard.connect_changed(glib::clone!(#[strong] handler, move |clipboard| {
+        if clipboard.is_local() {
+            return;
+        }
+        release();
+        if let Some(id) = handler.borrow_mut().take() {
+            clipboard.disconnect(id);
+        }
+    }));
+    *handler.borrow_mut() = Some(id);
+}
+
+fn clipboard() -> anyhow::Result<gdk::Clipboard> {
+    let display = gdk::Display::default().ok_or_else(|| anyhow::anyhow!("no display"))?;
+    Ok(display.clipboard())
+}
+
+/// Everything that was on the clipboard before an item was pasted through it,
+/// in every format it was offered in
+struct ClipboardSnapshot {
+    contents: Vec<(String, glib::Bytes)>,
+}
+
+impl ClipboardSnapshot {
+    async fn take() -> anyhow::Result<Self> {
+        on_main_thread(|| async {
+            hold();
+            let clipboard = clipboard()?;
+            let mime_types = clipboard.formats().mime_types();
+            let mut contents = Vec::new();
+            let mut size = 0;
+            for mime_type in &mime_types {
+                let data = match read(&clipboard, mime_type).await {
+                    Ok(data) => data,
+                    // some apps offer formats they can't produce
+                    Err(e) => {
+                        eprintln!("Failed to read clipboard as {}: {}", mime_type, e);
+                        continue;
+                    }
+                };
+                size += data.len();
+                if size > MAX_SNAPSHOT_SIZE {
+                    anyhow::bail!("the clipboard is too large to keep");
+                }
+                contents.push((mime_type.to_string(), data));
+            }
+            if contents.is_empty() && !mime_types.is_empty() {
+                anyhow::bail!("the clipboard could not be read");
+            }
+            Ok(Self { contents })
+        }).await
+    }
+
+    // put the contents back, unless something other than `item` was copied since
+    async fn restore(self, item: String) -> anyhow::Result<()> {
+        on_main_thread(move || async move {
+            let clipboard = clipboard()?;
+            let current = clipboard.read_text_future().await.ok().flatten();
+            if current.as_deref() != Some(item.as_str()) {
+                release();
+                return Ok(());
+            }
+            if self.contents.is_empty() {
+                clipboard.set_content(None::<&gdk::ContentProvider>)?;
+                release();
+                return Ok(());
+            }
+            let providers: Vec<gdk::ContentProvider> = self.contents.iter()
+                .map(|(mime_type, data)| gdk::ContentProvider::for_bytes(mime_type, data))
+                .collect();
+            clipboard.set_content(Some(&gdk::ContentProvider::new_union(&providers)))?;
+            release_when_replaced(&clipboard);
+            Ok(())
+        }).await
+    }
+}
+
+/// Snapshot the clipboard before pasting through it, returns the paste's id
+pub async fn snapshot() -> anyhow::Result<u64> {
+    let mut pending = PENDING.lock().await;
+    let snapshot = match pending.take() {
+        Some((_, snapshot)) => snapshot,
+        None => {
+            let snapshot = tokio::time::timeout(SNAPSHOT_TIMEOUT, ClipboardSnapshot::take()).await
+                .map_err(|_| anyhow::anyhow!("the clipboard owner did not answer"))
+                .and_then(|snapshot| snapshot);
+            match snapshot {
+                Ok(snapshot) => snapshot,
+                Err(e) => {
+                    glib::MainContext::default().invoke(release);
+                    return Err(e);
+                }
+            }
+        }
+    };
+    let id = PASTE_COUNTER.fetch_add(1, Ordering::Relaxed);
+    *pending = Some((id, snapshot));
+    Ok(id)
+}
+
+/// Restore the snapshot of paste `id` once `item` was pasted, unless a later paste took it over
+pub async fn restore(id: u64, item: String) -> anyhow::Result<()> {
+    let snapshot = {
+        let mut pending = PENDING.lock().await;
+        match pending.take() {
+            Some((pending_id, snapshot)) if pending_id == id => snapshot,
+            other => {
+                *pending = other;
+                return Ok(());
+            }
+        }
+    };
+    let result = snapshot.restore(item).await;
+    if result.is_err() {
+        glib::MainContext::default().invoke(release);
+    }
+    result
+}
+
+/// Forget the snapshot of paste `id`, the item stays on the clipboard
+pub async fn discard(id: u64) {
+    let mut pending = PENDING.lock().await;
+    if pending.as_ref().is_some_and(|(pending_id, _)| *pending_id == id) {
+        *pending = None;
+        glib::MainContext::default().invoke(release);
+    }
+}
+
+async fn read(clipboard: &gdk::Clipboard, mime_type: &str) -> anyhow::Result<glib::Bytes> {
+    let (stream, _) = clipboard.read_future(&[mime_type], glib::Priority::DEFAULT).await?;
+    let data = gio::MemoryOutputStream::new_resizable();
+    data.splice_future(
+        &stream,
+        gio::OutputStreamSpliceFlags::CLOSE_SOURCE | gio::OutputStreamSpliceFlags::CLOSE_TARGET,
+        glib::Priority::DEFAULT,
+    ).await?;
+    Ok(data.steal_as_bytes())
+}
+
+fn copy_to_clipboard(text: &str) {
+    let display = gdk::Display::default().expect("No display");
+    let clipboard = display.clipboard();
+    clipboard.set_text(text);
+    println!("Copied to clipboard: {}", text);
+}
+
+impl InsertionBackend for ClipboardBackend {
+    fn id(&self) -> &'static str {
+        Self::ID
+    }
+
+    fn name(&self) -> &'static str {
+        "Clipboard"
+    }
+
+    // the fallback anyway, never detected
+    fn auto_detect(&self) -> bool {
+        false
+    }
+
+    fn is_available(&self) -> BoxFuture<'_, bool> {
+        Box::pin(async { true })
+    }
+
+    fn insert<'a>(&'a self, text: &'a str) -> BoxFuture<'a, anyhow::Result<()>> {
+        Self::copy_and_dismiss(text.to_string(), "Copied to clipboard".to_string());
+        Box::pin(async { Ok(()) })
+    }
+}
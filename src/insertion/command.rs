@@ -0,0 +1,114 @@
+use std::env;
+use std::path::Path;
+use std::process::Command;
+use std::time::Duration;
+use super::{BoxFuture, InsertionBackend};
+
+/// Types text with an external tool: wtype (wlroots virtual keyboard),
+/// xdotool (X11 XTest) or ydotool (uinput, needs its daemon)
+pub struct CommandBackend {
+    id: &'static str,
+    name: &'static str,
+    program: &'static str,
+    // put before the text
+    args: &'static [&'static str],
+    // whether the session can be typed into with this tool
+    supported: fn() -> bool,
+    auto_detect: bool,
+}
+
+fn is_wayland() -> bool {
+    env::var_os("WAYLAND_DISPLAY").is_some()
+}
+
+fn is_desktop(name: &str) -> bool {
+    env::var("XDG_CURRENT_DESKTOP").is_ok_and(|d| d.split(':').any(|d| d.eq_ignore_ascii_case(name)))
+}
+
+fn in_path(program: &str) -> bool {
+    env::var_os("PATH").is_some_and(|path| env::split_paths(&path).any(|dir| dir.join(program).is_file()))
+}
+
+impl CommandBackend {
+    pub fn wtype() -> Self {
+        Self {
+            id: "wtype",
+            name: "wtype",
+            program: "wtype",
+            args: &["--"],
+            // GNOME & KDE don't offer the virtual keyboard protocol to clients
+            supported: || is_wayland() && !is_desktop("GNOME") && !is_desktop("KDE"),
+            auto_detect: true,
+        }
+    }
+
+    pub fn xdotool() -> Self {
+        Self {
+            id: "xdotool",
+            name: "X11 (xdotool)",
+            program: "xdotool",
+            args: &["type", "--clearmodifiers", "--"],
+            supported: || env::var_os("DISPLAY").is_some() && !is_wayland(),
+            auto_detect: true,
+        }
+    }
+
+    /// Only types characters of the keyboard layout, so no emoji. Never
+    /// auto-detected, the clipboard does better for most items.
+    pub fn ydotool() -> Self {
+        Self {
+            id: "ydotool",
+            name: "ydotool",
+            program: "ydotool",
+            args: &["type", "--"],
+            supported: || {
+                let socket = env::var_os("YDOTOOL_SOCKET")
+                    .or_else(|| env::var_os("XDG_RUNTIME_DIR").map(|dir| Path::new(&dir).join(".ydotool_socket").into()));
+                socket.is_some_and(|s| Path::new(&s).exists()) || Path::new("/tmp/.ydotool_socket").exists()
+            },
+            auto_detect: false,
+        }
+    }
+}
+
+impl InsertionBackend for CommandBackend {
+    fn id(&self) -> &'static str {
+        self.id
+    }
+
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn auto_detect(&self) -> bool {
+        self.auto_detect
+    }
+
+    fn is_available(&self) -> BoxFuture<'_, bool> {
+        Box::pin(async { (self.supported)() && in_path(self.program) })
+    }
+
+    fn insert<'a>(&'a self, text: &'a str) -> BoxFuture<'a, anyhow::Result<()>> {
+        let program = self.program;
+        let args = self.args;
+        let text = text.to_string();
+        Box::pin(async move {
+            let status = tokio::task::spawn_blocking(move || {
+                Command::new(program).args(args).arg(&text).status()
+            }).await??;
+            if !status.success() {
+                anyhow::bail!("{} exited with {}", program, status);
+            }
+            Ok(())
+        })
+    }
+
+    fn needs_focus(&self) -> bool {
+        true
+    }
+
+    // typing long kaomoji takes a while
+    fn timeout(&self) -> Duration {
+        Duration::from_secs(5)
+    }
+}
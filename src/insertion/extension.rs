@@ -0,0 +1,28 @@
+use crate::dbus::{self, DBusClient};
+use super::{BoxFuture, InsertionBackend};
+
+/// The companion GNOME Shell extension: refocuses the previous window and pastes
+pub struct ExtensionBackend;
+
+impl InsertionBackend for ExtensionBackend {
+    fn id(&self) -> &'static str {
+        "extension"
+    }
+
+    fn name(&self) -> &'static str {
+        "GNOME Shell extension"
+    }
+
+    // decided by the startup handshake, assumed to work until it finishes
+    fn is_available(&self) -> BoxFuture<'_, bool> {
+        Box::pin(async { dbus::extension_status().has_capability("insert-text") })
+    }
+
+    fn insert<'a>(&'a self, text: &'a str) -> BoxFuture<'a, anyhow::Result<()>> {
+        Box::pin(DBusClient::insert_via_extension(text))
+    }
+
+    fn uses_clipboard(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,304 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+use tokio::sync::{oneshot, Mutex};
+use zbus::message::Header;
+use zbus::object_server::SignalContext;
+use zbus::zvariant::{OwnedObjectPath, OwnedValue, StructureBuilder, Value};
+use zbus::{fdo, Connection, ObjectServer};
+use super::{BoxFuture, InsertionBackend};
+
+const ENGINE_NAME: &str = "carmenta";
+const COMPONENT_NAME: &str = "org.freedesktop.IBus.Carmenta";
+const FACTORY_PATH: &str = "/org/freedesktop/IBus/Factory";
+// how long the daemon gets to switch the focused input context to our engine
+const SWITCH_TIMEOUT: Duration = Duration::from_secs(1);
+
+// private connection to ibus-daemon, it owns our engine factory
+static BUS: Mutex<Option<Connection>> = Mutex::const_new(None);
+// text waiting for the engine to be focused
+static PENDING: Mutex<Option<Commit>> = Mutex::const_new(None);
+static ENGINE_COUNTER: AtomicU32 = AtomicU32::new(0);
+
+struct Commit {
+    text: String,
+    done: oneshot::Sender<()>,
+}
+
+#[zbus::proxy(
+    interface = "org.freedesktop.IBus",
+    default_service = "org.freedesktop.IBus",
+    default_path = "/org/freedesktop/IBus"
+)]
+trait IBus {
+    fn register_component(&self, component: &Value<'_>) -> zbus::Result<()>;
+
+    fn set_global_engine(&self, engine_name: &str) -> zbus::Result<()>;
+
+    #[zbus(property(emits_changed_signal = "false"))]
+    fn global_engine(&self) -> zbus::Result<OwnedValue>;
+}
+
+/// Commits text through IBus: switches to a tiny Carmenta engine, which
+/// commits like any input method would, then back to the user's engine
+pub struct IBusBackend;
+
+// where ibus-daemon writes its address, see ibus_get_socket_path()
+fn address_file() -> Option<PathBuf> {
+    let machine_id = fs::read_to_string("/etc/machine-id")
+        .or_else(|_| fs::read_to_string("/var/lib/dbus/machine-id"))
+        .ok()?;
+    // inside the Flatpak XDG_CONFIG_HOME is the app's own, the host's
+    // ~/.config/ibus is shared at its usual place
+    let configs: Vec<PathBuf> = env::var_os("XDG_CONFIG_HOME").map(PathBuf::from).into_iter()
+        .chain(env::var_os("HOME").map(|home| Path::new(&home).join(".config")))
+        .collect();
+
+    // a Wayland daemon names the file after the Wayland display, an X11 one
+    // after the host & display number of DISPLAY
+    let mut displays = Vec::new();
+    if let Ok(wayland) = env::var("WAYLAND_DISPLAY") {
+        displays.push(format!("unix-{}", wayland));
+    }
+    let display = env::var("DISPLAY").unwrap_or_else(|_| ":0.0".to_string());
+    if let Some((host, rest)) = display.split_once(':') {
+        let host = if host.is_empty() { "unix" } else { host };
+        let number = rest.split('.').next().unwrap_or("0");
+        displays.push(format!("{}-{}", host, number));
+    }
+
+    let machine_id = machine_id.trim();
+    configs.iter()
+        .flat_map(|config| displays.iter().map(move |display| {
+            config.join("ibus").join("bus").join(format!("{}-{}", machine_id, display))
+        }))
+        .find(|path| path.is_file())
+}
+
+fn address() -> Option<String> {
+    if let Ok(address) = env::var("IBUS_ADDRESS") {
+        return Some(address);
+    }
+    let contents = fs::read_to_string(address_file()?).ok()?;
+    contents.lines()
+        .find_map(|line| line.strip_prefix("IBUS_ADDRESS="))
+        .map(str::to_string)
+}
+
+// IBusSerializable objects are structs of type name, attachments & fields
+fn serializable(type_name: &str) -> StructureBuilder<'_> {
+    StructureBuilder::new()
+        .add_field(type_name)
+        .add_field(HashMap::<&str, Value>::new())
+}
+
+// IBusText without attributes
+fn ibus_text(text: &str) -> Value<'_> {
+    let attributes = serializable("IBusAttrList").add_field(Vec::<Value>::new()).build();
+    serializable("IBusText")
+        .add_field(text)
+        .add_field(Value::from(attributes))
+        .build()
+        .into()
+}
+
+// IBusComponent announcing the engine, this connection is its factory
+fn component() -> Value<'static> {
+    let engine = serializable("IBusEngineDesc")
+        .add_field(ENGINE_NAME)
+        .add_field("Carmenta") // long name
+        .add_field("Inserts items picked in Carmenta")
+        .add_field("other") // language
+        .add_field("MIT")
+        .add_field("") // author
+        .add_field("") // icon
+        .add_field("default") // keep the current keyboard layout
+        .add_field(0u32) // rank
+        .add_field("") // hotkeys
+        .add_field("") // symbol
+        .add_field("") // setup
+        .add_field("") // layout variant
+        .add_field("") // layout option
+        .add_field(env!("CARGO_PKG_VERSION"))
+        .add_field("") // text domain
+        .add_field("") // icon property key
+        .build();
+    serializable("IBusComponent")
+        .add_field(COMPONENT_NAME)
+        .add_field("Carmenta")
+        .add_field(env!("CARGO_PKG_VERSION"))
+        .add_field("MIT")
+        .add_field("") // author
+        .add_field("") // homepage
+        .add_field("") // command line, we are started by the user
+        .add_field("") // text domain
+        .add_field(Vec::<Value>::new()) // observed paths
+        .add_field(vec![Value::from(engine)])
+        .build()
+        .into()
+}
+
+// the name field of a serialized IBusEngineDesc, possibly inside variants
+fn engine_name(value: &Value<'_>) -> Option<String> {
+    match value {
+        Value::Value(inner) => engine_name(inner),
+        Value::Structure(desc) => match desc.fields().get(2) {
+            Some(Value::Str(name)) => Some(name.to_string()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+struct Factory;
+
+#[zbus::interface(name = "org.freedesktop.IBus.Factory")]
+impl Factory {
+    async fn create_engine(&self, name: &str, #[zbus(object_server)] server: &ObjectServer) -> fdo::Result<OwnedObjectPath> {
+        if name != ENGINE_NAME {
+            return Err(fdo::Error::InvalidArgs(format!("no engine called '{}'", name)));
+        }
+        let path = format!("/org/freedesktop/IBus/Engine/{}", ENGINE_COUNTER.fetch_add(1, Ordering::Relaxed));
+        let path = OwnedObjectPath::try_from(path).map_err(|e| fdo::Error::Failed(e.to_string()))?;
+        server.at(&path, Engine).await?;
+        server.at(&path, EngineService).await?;
+        Ok(path)
+    }
+}
+
+struct Engine;
+
+impl Engine {
+    // the engine is focused once the daemon has made it the focused context's engine
+    async fn commit_pending(ctxt: &SignalContext<'_>) {
+        let Some(commit) = PENDING.lock().await.take() else { return };
+        match Self::commit_text(ctxt, &ibus_text(&commit.text)).await {
+            Ok(()) => {
+                let _ = commit.done.send(());
+            }
+            Err(e) => eprintln!("IBus commit error: {}", e),
+        }
+    }
+}
+
+#[zbus::interface(name = "org.freedesktop.IBus.Engine")]
+impl Engine {
+    async fn enable(&self, #[zbus(signal_context)] ctxt: SignalContext<'_>) {
+        Self::commit_pending(&ctxt).await;
+    }
+
+    async fn focus_in(&self, #[zbus(signal_context)] ctxt: SignalContext<'_>) {
+        Self::commit_pending(&ctxt).await;
+    }
+
+    fn focus_out(&self) {}
+
+    fn disable(&self) {}
+
+    fn reset(&self) {}
+
+    // keys typed while the engine is active go to the application untouched
+    fn process_key_event(&self, _keyval: u32, _keycode: u32, _state: u32) -> bool {
+        false
+    }
+
+    fn set_capabilities(&self, _capabilities: u32) {}
+
+    fn set_cursor_location(&self, _x: i32, _y: i32, _width: i32, _height: i32) {}
+
+    fn set_surrounding_text(&self, _text: Value<'_>, _cursor_pos: u32, _anchor_pos: u32) {}
+
+    #[zbus(signal)]
+    async fn commit_text(ctxt: &SignalContext<'_>, text: &Value<'_>) -> zbus::Result<()>;
+}
+
+struct EngineService;
+
+#[zbus::interface(name = "org.freedesktop.IBus.Service")]
+impl EngineService {
+    // the daemon is done with the engine
+    async fn destroy(&self, #[zbus(object_server)] server: &ObjectServer, #[zbus(header)] header: Header<'_>) -> fdo::Result<()> {
+        if let Some(path) = header.path() {
+            server.remove::<Engine, _>(path).await?;
+            server.remove::<EngineService, _>(path).await?;
+        }
+        Ok(())
+    }
+}
+
+async fn connect() -> anyhow::Result<Connection> {
+    let address = address().ok_or_else(|| anyhow::anyhow!("IBus is not running"))?;
+    let conn = zbus::connection::Builder::address(address.as_str())?
+        .serve_at(FACTORY_PATH, Factory)?
+        .build()
+        .await?;
+    IBusProxy::new(&conn).await?.register_component(&component()).await?;
+    Ok(conn)
+}
+
+async fn commit(text: &str) -> anyhow::Result<()> {
+    let mut bus = BUS.lock().await;
+    let conn = match bus.as_ref() {
+        Some(conn) => conn.clone(),
+        None => {
+            let conn = connect().await?;
+            *bus = Some(conn.clone());
+            conn
+        }
+    };
+    let ibus = IBusProxy::new(&conn).await?;
+    let previous = ibus.global_engine().await.ok().and_then(|engine| engine_name(&engine));
+
+    let (done, committed) = oneshot::channel();
+    *PENDING.lock().await = Some(Commit { text: text.to_string(), done });
+    let result = async {
+        ibus.set_global_engine(ENGINE_NAME).await?;
+        tokio::time::timeout(SWITCH_TIMEOUT, committed).await
+            .map_err(|_| anyhow::anyhow!("no text field has focus"))?
+            .map_err(|_| anyhow::anyhow!("the engine was not started"))
+    }.await;
+    PENDING.lock().await.take();
+
+    if let Some(previous) = previous.filter(|name| name != ENGINE_NAME) {
+        if let Err(e) = ibus.set_global_engine(&previous).await {
+            eprintln!("Failed to restore IBus engine {}: {}", previous, e);
+        }
+    }
+    if result.is_err() {
+        // the daemon may have restarted, register again next time
+        *bus = None;
+    }
+    result
+}
+
+impl InsertionBackend for IBusBackend {
+    fn id(&self) -> &'static str {
+        "ibus"
+    }
+
+    fn name(&self) -> &'static str {
+        "IBus"
+    }
+
+    fn is_available(&self) -> BoxFuture<'_, bool> {
+        Box::pin(async { address().is_some() })
+    }
+
+    // in its own task, so a timeout can't cancel it before the user's engine is back
+    fn insert<'a>(&'a self, text: &'a str) -> BoxFuture<'a, anyhow::Result<()>> {
+        let text = text.to_string();
+        Box::pin(async move { tokio::spawn(async move { commit(&text).await }).await? })
+    }
+
+    fn needs_focus(&self) -> bool {
+        true
+    }
+
+    // leaves room to switch back to the user's engine after a failed switch
+    fn timeout(&self) -> Duration {
+        SWITCH_TIMEOUT * 3
+    }
+}
@@ -0,0 +1,91 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use super::{BoxFuture, InsertionBackend};
+
+#[derive(Clone, Copy)]
+enum Outcome {
+    Insert,
+    Fail,
+    Hang,
+}
+
+/// Records what it inserts instead of touching any application, for tests
+#[derive(Clone)]
+pub struct MockBackend {
+    id: &'static str,
+    available: bool,
+    auto_detect: bool,
+    outcome: Outcome,
+    inserted: Arc<Mutex<Vec<String>>>,
+}
+
+impl MockBackend {
+    pub fn new(id: &'static str) -> Self {
+        Self { id, available: true, auto_detect: false, outcome: Outcome::Insert, inserted: Arc::default() }
+    }
+
+    pub fn available(mut self, available: bool) -> Self {
+        self.available = available;
+        self
+    }
+
+    pub fn auto_detected(mut self, auto_detect: bool) -> Self {
+        self.auto_detect = auto_detect;
+        self
+    }
+
+    /// Insertions return an error
+    pub fn failing(mut self) -> Self {
+        self.outcome = Outcome::Fail;
+        self
+    }
+
+    /// Insertions never finish
+    pub fn hanging(mut self) -> Self {
+        self.outcome = Outcome::Hang;
+        self
+    }
+
+    /// Texts inserted so far, shared by clones
+    pub fn inserted(&self) -> Vec<String> {
+        self.inserted.lock().map(|i| i.clone()).unwrap_or_default()
+    }
+}
+
+impl InsertionBackend for MockBackend {
+    fn id(&self) -> &'static str {
+        self.id
+    }
+
+    fn name(&self) -> &'static str {
+        "Mock"
+    }
+
+    fn auto_detect(&self) -> bool {
+        self.auto_detect
+    }
+
+    fn is_available(&self) -> BoxFuture<'_, bool> {
+        Box::pin(async { self.available })
+    }
+
+    fn insert<'a>(&'a self, text: &'a str) -> BoxFuture<'a, anyhow::Result<()>> {
+        Box::pin(async move {
+            match self.outcome {
+                Outcome::Insert => {
+                    if let Ok(mut inserted) = self.inserted.lock() {
+                        inserted.push(text.to_string());
+                    }
+                    Ok(())
+                }
+                Outcome::Fail => anyhow::bail!("mock failure"),
+                Outcome::Hang => std::future::pending().await,
+            }
+        })
+    }
+
+    // keeps timeout tests quick
+    fn timeout(&self) -> Duration {
+        Duration::from_millis(50)
+    }
+}
@@ -0,0 +1,269 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
+use gtk4::glib;
+use crate::dbus;
+
+mod clipboard;
+mod command;
+mod extension;
+mod ibus;
+#[cfg(test)]
+mod mock;
+mod portal;
+
+pub use clipboard::{ClipboardBackend, RESTORE_DELAYS};
+pub use command::CommandBackend;
+pub use extension::ExtensionBackend;
+pub use ibus::IBusBackend;
+#[cfg(test)]
+pub use mock::MockBackend;
+pub use portal::PortalBackend;
+
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+// time for the compositor to hand focus back to the previous window
+const FOCUS_DELAY: Duration = Duration::from_millis(150);
+
+/// A way of getting text into the application the user was typing in
+pub trait InsertionBackend: Send + Sync {
+    /// Id stored in the settings
+    fn id(&self) -> &'static str;
+
+    /// Name shown to the user
+    fn name(&self) -> &'static str;
+
+    /// Whether auto-detection may pick this backend
+    fn auto_detect(&self) -> bool {
+        true
+    }
+
+    /// Whether the backend can work in this session
+    fn is_available(&self) -> BoxFuture<'_, bool>;
+
+    fn insert<'a>(&'a self, text: &'a str) -> BoxFuture<'a, anyhow::Result<()>>;
+
+    /// Types into whatever has keyboard focus, so the picker has to hide first
+    fn needs_focus(&self) -> bool {
+        false
+    }
+
+    /// Pastes through the clipboard, whose previous contents get restored afterwards
+    fn uses_clipboard(&self) -> bool {
+        false
+    }
+
+    fn timeout(&self) -> Duration {
+        Duration::from_millis(500)
+    }
+}
+
+/// Every backend, in auto-detection order
+pub fn backends() -> Vec<Box<dyn InsertionBackend>> {
+    vec![
+        Box::new(ExtensionBackend),
+        Box::new(IBusBackend),
+        Box::new(CommandBackend::wtype()),
+        Box::new(CommandBackend::xdotool()),
+        Box::new(PortalBackend),
+        Box::new(CommandBackend::ydotool()),
+        Box::new(ClipboardBackend),
+    ]
+}
+
+/// The backend with id `preferred` (from the settings), otherwise the first
+/// available one of `candidates`. The clipboard is the last resort.
+pub async fn select(preferred: Option<&str>, candidates: Vec<Box<dyn InsertionBackend>>) -> Box<dyn InsertionBackend> {
+    if let Some(id) = preferred {
+        if let Some(i) = candidates.iter().position(|b| b.id() == id) {
+            return candidates.into_iter().nth(i).unwrap_or_else(|| Box::new(ClipboardBackend));
+        }
+        eprintln!("Unknown insertion backend '{}', detecting one", id);
+    }
+    for backend in candidates {
+        if backend.auto_detect() && backend.is_available().await {
+            return backend;
+        }
+    }
+    Box::new(ClipboardBackend)
+}
+
+// why nothing better than the clipboard was found
+fn clipboard_reason() -> &'static str {
+    let desktop = std::env::var("XDG_CURRENT_DESKTOP").unwrap_or_default();
+    if desktop.split(':').any(|d| d == "GNOME") {
+        dbus::extension_status().fallback_reason()
+    } else {
+        "no insertion method available"
+    }
+}
+
+// GDK objects & settings live on the main thread, insertion runs on the tokio runtime
+async fn on_main_thread<T, F, Fut>(f: F) -> anyhow::Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Fut + Send + 'static,
+    Fut: Future<Output = anyhow::Result<T>> + 'static,
+{
+    glib::MainContext::default().spawn_from_within(f).await
+        .map_err(|_| anyhow::anyhow!("the main loop went away"))?
+}
+
+fn notify(message: &'static str) {
+    glib::MainContext::default().invoke(move || crate::app::show_toast(message));
+}
+
+/// Insert `text` with the configured (or detected) backend, falling back
+/// to the clipboard when that fails
+pub fn insert_or_copy(text: &str) {
+    let text = text.to_string();
+    let preferred = crate::settings::get().insertion_backend;
+    let Some(rt) = crate::RUNTIME.get() else {
+        eprintln!("Runtime not initialized!");
+        return;
+    };
+
+    rt.spawn(insert_with(text, preferred, backends()));
+}
+
+/// Insert `text` with the backend [`select`] picks from `candidates`,
+/// falling back to the clipboard when that fails. Returns the id of the
+/// backend that got the text.
+pub async fn insert_with(text: String, preferred: Option<String>, candidates: Vec<Box<dyn InsertionBackend>>) -> &'static str {
+    let backend = select(preferred.as_deref(), candidates).await;
+    if backend.id() == ClipboardBackend::ID {
+        dbus::record_backend(backend.name());
+        let message = match preferred {
+            Some(_) => "Copied to clipboard".to_string(),
+            None => format!("Copied to clipboard — {}", clipboard_reason()),
+        };
+        ClipboardBackend::copy_and_dismiss(text, message);
+        return ClipboardBackend::ID;
+    }
+
+    if backend.needs_focus() {
+        glib::MainContext::default().invoke(crate::app::hide_window);
+        tokio::time::sleep(FOCUS_DELAY).await;
+    }
+
+    let restore_delay = if backend.uses_clipboard() { clipboard::restore_delay().await } else { None };
+    let paste = match restore_delay {
+        Some(_) => match clipboard::snapshot().await {
+            Ok(paste) => Some(paste),
+            Err(e) => {
+                dbus::record_error(format!("Clipboard snapshot error: {}", e));
+                notify("Your clipboard will be replaced by the item");
+                None
+            }
+        },
+        None => None,
+    };
+
+    let result = tokio::time::timeout(backend.timeout(), backend.insert(&text)).await;
+    let reason = match result {
+        Ok(Ok(())) => {
+            dbus::record_backend(backend.name());
+            if let (Some(paste), Some(delay)) = (paste, restore_delay) {
+                tokio::time::sleep(delay).await;
+                if let Err(e) = clipboard::restore(paste, text).await {
+                    dbus::record_error(format!("Clipboard restore error: {}", e));
+                    notify("Could not restore your clipboard, it still holds the item");
+                }
+            }
+            if backend.needs_focus() {
+                glib::MainContext::default().invoke(crate::app::dismiss);
+            }
+            return backend.id();
+        }
+        Ok(Err(e)) => {
+            dbus::record_error(format!("{} error: {}", backend.name(), e));
+            format!("{} failed", backend.name())
+        }
+        Err(_) => {
+            dbus::record_error(format!("{} timeout: no answer in {:?}", backend.name(), backend.timeout()));
+            format!("{} did not respond", backend.name())
+        }
+    };
+    dbus::record_backend(ClipboardBackend.name());
+    ClipboardBackend::copy_and_dismiss(text, format!("Copied to clipboard — {}", reason));
+    // after the copy, a dismissed picker may quit once the snapshot is gone
+    if let Some(paste) = paste {
+        clipboard::discard(paste).await;
+    }
+    ClipboardBackend::ID
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn boxed(backends: &[&MockBackend]) -> Vec<Box<dyn InsertionBackend>> {
+        backends.iter().map(|&b| Box::new(b.clone()) as Box<dyn InsertionBackend>).collect()
+    }
+
+    #[tokio::test]
+    async fn preferred_backend_is_used_even_when_unavailable() {
+        let first = MockBackend::new("first").auto_detected(true);
+        let preferred = MockBackend::new("preferred").available(false);
+        let backend = select(Some("preferred"), boxed(&[&first, &preferred])).await;
+        assert_eq!(backend.id(), "preferred");
+
+        backend.insert("🎉").await.unwrap();
+        assert_eq!(preferred.inserted(), ["🎉"]);
+        assert!(first.inserted().is_empty());
+    }
+
+    #[tokio::test]
+    async fn unknown_preferred_backend_falls_back_to_detection() {
+        let detected = MockBackend::new("detected").auto_detected(true);
+        let backend = select(Some("gone"), boxed(&[&detected])).await;
+        assert_eq!(backend.id(), "detected");
+    }
+
+    #[tokio::test]
+    async fn detection_takes_the_first_available_auto_detected_backend() {
+        let unavailable = MockBackend::new("unavailable").auto_detected(true).available(false);
+        let manual = MockBackend::new("manual");
+        let second = MockBackend::new("second").auto_detected(true);
+        let third = MockBackend::new("third").auto_detected(true);
+        let backend = select(None, boxed(&[&unavailable, &manual, &second, &third])).await;
+        assert_eq!(backend.id(), "second");
+    }
+
+    #[tokio::test]
+    async fn clipboard_is_the_last_resort() {
+        let unavailable = MockBackend::new("unavailable").auto_detected(true).available(false);
+        let manual = MockBackend::new("manual");
+        let backend = select(None, boxed(&[&unavailable, &manual])).await;
+        assert_eq!(backend.id(), ClipboardBackend::ID);
+        assert_eq!(select(None, Vec::new()).await.id(), ClipboardBackend::ID);
+    }
+
+    #[tokio::test]
+    async fn working_backend_gets_the_text() {
+        let mock = MockBackend::new("mock");
+        let used = insert_with("🎉".to_string(), Some("mock".to_string()), boxed(&[&mock])).await;
+        assert_eq!(used, "mock");
+        assert_eq!(mock.inserted(), ["🎉"]);
+    }
+
+    #[tokio::test]
+    async fn failing_backend_falls_back_to_the_clipboard() {
+        let failing = MockBackend::new("failing").failing();
+        let used = insert_with("🎉".to_string(), Some("failing".to_string()), boxed(&[&failing])).await;
+        assert_eq!(used, ClipboardBackend::ID);
+        assert!(failing.inserted().is_empty());
+    }
+
+    #[tokio::test]
+    async fn hanging_backend_falls_back_to_the_clipboard() {
+        let hanging = MockBackend::new("hanging").hanging();
+        let used = insert_with("🎉".to_string(), Some("hanging".to_string()), boxed(&[&hanging])).await;
+        assert_eq!(used, ClipboardBackend::ID);
+    }
+
+    #[test]
+    fn ydotool_is_never_auto_detected() {
+        assert!(!CommandBackend::ydotool().auto_detect());
+    }
+}
@@ -0,0 +1,206 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+use tokio::sync::Mutex;
+use gtk4::glib;
+use zbus::export::futures_util::StreamExt;
+use zbus::zvariant::{ObjectPath, OwnedObjectPath, OwnedValue, Value};
+use zbus::Connection;
+use crate::dbus::DBusClient;
+use super::{on_main_thread, BoxFuture, InsertionBackend};
+
+// SelectDevices device types
+const DEVICE_KEYBOARD: u32 = 1;
+// NotifyKeyboardKeysym key states
+const KEY_RELEASED: u32 = 0;
+const KEY_PRESSED: u32 = 1;
+
+// SelectDevices persist_mode: until the user revokes the permission
+const PERSIST_PERMANENTLY: u32 = 2;
+
+// the session survives between insertions, and its restore token (kept in
+// the settings) lets later launches start one without asking again
+static SESSION: Mutex<Option<OwnedObjectPath>> = Mutex::const_new(None);
+static TOKEN_COUNTER: AtomicU32 = AtomicU32::new(0);
+
+#[zbus::proxy(
+    interface = "org.freedesktop.portal.RemoteDesktop",
+    default_service = "org.freedesktop.portal.Desktop",
+    default_path = "/org/freedesktop/portal/desktop"
+)]
+trait RemoteDesktop {
+    fn create_session(&self, options: HashMap<&str, Value<'_>>) -> zbus::Result<OwnedObjectPath>;
+
+    fn select_devices(&self, session_handle: &ObjectPath<'_>, options: HashMap<&str, Value<'_>>) -> zbus::Result<OwnedObjectPath>;
+
+    fn start(&self, session_handle: &ObjectPath<'_>, parent_window: &str, options: HashMap<&str, Value<'_>>) -> zbus::Result<OwnedObjectPath>;
+
+    fn notify_keyboard_keysym(&self, session_handle: &ObjectPath<'_>, options: HashMap<&str, Value<'_>>, keysym: i32, state: u32) -> zbus::Result<()>;
+
+    #[zbus(property)]
+    fn version(&self) -> zbus::Result<u32>;
+}
+
+#[zbus::proxy(
+    interface = "org.freedesktop.portal.Request",
+    default_service = "org.freedesktop.portal.Desktop"
+)]
+trait Request {
+    #[zbus(signal)]
+    fn response(&self, response: u32, results: HashMap<String, OwnedValue>) -> zbus::Result<()>;
+}
+
+/// Keyboard injection through the XDG RemoteDesktop portal, works on any
+/// desktop with a portal backend but asks the user for permission first
+pub struct PortalBackend;
+
+// X keysym for a character, Unicode keysyms above Latin-1
+fn keysym(c: char) -> i32 {
+    match c as u32 {
+        cp @ (0x20..=0x7e | 0xa0..=0xff) => cp as i32,
+        cp => (0x0100_0000 | cp) as i32,
+    }
+}
+
+fn handle_token() -> String {
+    format!("carmenta{}", TOKEN_COUNTER.fetch_add(1, Ordering::Relaxed))
+}
+
+// Portal methods answer through a Request object: subscribe to its Response
+// before calling, then wait for the results
+async fn request<F, Fut>(conn: &Connection, call: F) -> anyhow::Result<HashMap<String, OwnedValue>>
+where
+    F: FnOnce(String) -> Fut,
+    Fut: Future<Output = zbus::Result<OwnedObjectPath>>,
+{
+    let token = handle_token();
+    let sender = conn.unique_name()
+        .ok_or_else(|| anyhow::anyhow!("no unique bus name"))?
+        .trim_start_matches(':')
+        .replace('.', "_");
+    let path = format!("/org/freedesktop/portal/desktop/request/{}/{}", sender, token);
+    let request = RequestProxy::builder(conn).path(path)?.build().await?;
+    let mut responses = request.receive_response().await?;
+
+    call(token).await?;
+    let response = responses.next().await.ok_or_else(|| anyhow::anyhow!("portal request vanished"))?;
+    let args = response.args()?;
+    match args.response {
+        0 => Ok(args.results),
+        1 => anyhow::bail!("remote desktop access was denied"),
+        _ => anyhow::bail!("the portal failed to set up remote desktop access"),
+    }
+}
+
+async fn restore_token() -> Option<String> {
+    on_main_thread(|| async { Ok(crate::settings::get().portal_restore_token) }).await.ok().flatten()
+}
+
+// tokens are single use, every started session hands out the next one
+fn save_restore_token(token: Option<String>) {
+    glib::MainContext::default().invoke(move || {
+        if crate::settings::get().portal_restore_token != token {
+            crate::settings::update(|s| s.portal_restore_token = token);
+        }
+    });
+}
+
+// Create, configure & start a keyboard session. The portal asks the user
+// here, unless the restore token of an earlier session is still valid.
+async fn start_session(conn: &Connection, portal: &RemoteDesktopProxy<'_>) -> anyhow::Result<OwnedObjectPath> {
+    let results = request(conn, |token| async move {
+        let session_token = handle_token();
+        portal.create_session(HashMap::from([
+            ("handle_token", Value::from(token.as_str())),
+            ("session_handle_token", Value::from(session_token.as_str())),
+        ])).await
+    }).await?;
+    let session: String = results.get("session_handle")
+        .ok_or_else(|| anyhow::anyhow!("the portal returned no session"))?
+        .try_clone()?
+        .try_into()?;
+    let session = OwnedObjectPath::try_from(session)?;
+    let handle = &session;
+
+    let restore_token = restore_token().await;
+    request(conn, |token| async move {
+        let mut options = HashMap::from([
+            ("handle_token", Value::from(token.as_str())),
+            ("types", Value::from(DEVICE_KEYBOARD)),
+            ("persist_mode", Value::from(PERSIST_PERMANENTLY)),
+        ]);
+        if let Some(restore_token) = restore_token.as_deref() {
+            options.insert("restore_token", Value::from(restore_token));
+        }
+        portal.select_devices(handle, options).await
+    }).await?;
+    let results = request(conn, |token| async move {
+        portal.start(handle, "", HashMap::from([
+            ("handle_token", Value::from(token.as_str())),
+        ])).await
+    }).await?;
+    let restore_token = results.get("restore_token")
+        .and_then(|token| String::try_from(token.try_clone().ok()?).ok());
+    save_restore_token(restore_token);
+    Ok(session)
+}
+
+async fn type_text(text: &str) -> anyhow::Result<()> {
+    let conn = DBusClient::get_connection().await?;
+    let portal = RemoteDesktopProxy::new(&conn).await?;
+
+    let mut session = SESSION.lock().await;
+    let handle = match session.as_ref() {
+        Some(handle) => handle.clone(),
+        None => {
+            let handle = start_session(&conn, &portal).await?;
+            *session = Some(handle.clone());
+            handle
+        }
+    };
+
+    for c in text.chars() {
+        for state in [KEY_PRESSED, KEY_RELEASED] {
+            if let Err(e) = portal.notify_keyboard_keysym(&handle, HashMap::new(), keysym(c), state).await {
+                // closed by the user or the compositor, start over next time
+                *session = None;
+                return Err(e.into());
+            }
+        }
+    }
+    Ok(())
+}
+
+impl InsertionBackend for PortalBackend {
+    fn id(&self) -> &'static str {
+        "portal"
+    }
+
+    fn name(&self) -> &'static str {
+        "Remote Desktop portal"
+    }
+
+    fn is_available(&self) -> BoxFuture<'_, bool> {
+        Box::pin(async {
+            let Ok(conn) = DBusClient::get_connection().await else { return false };
+            match RemoteDesktopProxy::new(&conn).await {
+                Ok(portal) => portal.version().await.is_ok(),
+                Err(_) => false,
+            }
+        })
+    }
+
+    fn insert<'a>(&'a self, text: &'a str) -> BoxFuture<'a, anyhow::Result<()>> {
+        Box::pin(type_text(text))
+    }
+
+    fn needs_focus(&self) -> bool {
+        true
+    }
+
+    // the first insertion waits for the user to allow access
+    fn timeout(&self) -> Duration {
+        Duration::from_secs(60)
+    }
+}
@@ -1,8 +1,15 @@
 mod app;
+mod cli;
 mod window;
 mod dbus;
+mod insertion;
 mod ui;
 mod history;
+mod favorites;
+mod storage;
+mod settings;
+mod search;
+mod shortcode;
 
 #[allow(unused_imports)]
 use app::CarmentaApp;
@@ -13,6 +20,12 @@ const APP_ID: &str = "io.github.szymonwilczek.carmenta";
 pub static RUNTIME: OnceLock<tokio::runtime::Runtime> = OnceLock::new();
 
 fn main() -> anyhow::Result<()> {
+    // headless subcommands (search, get) skip GTK entirely
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(result) = cli::run(&args) {
+        return result;
+    }
+
     let rt = tokio::runtime::Runtime::new().expect("Failed to create Tokio runtime");
     RUNTIME.set(rt).expect("Failed to set global runtime");
 
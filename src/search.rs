@@ -0,0 +1,171 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use gtk4::prelude::*;
+use gtk4::{glib, CustomSorter};
+use crate::history::ItemKind;
+
+// Score bands, best first. Within the term based bands shorter terms win,
+// the penalty for extra length stays below the gap between two bands.
+const EXACT: u32 = 1000;
+const PREFIX: u32 = 900;
+const WORD_START: u32 = 700;
+const CONTAINS: u32 = 500;
+const ALL_WORDS: u32 = 300;
+const FUZZY: u32 = 150;
+const MAX_LENGTH_PENALTY: usize = 99;
+// per edit of a typo tolerant match
+const TYPO_PENALTY: u32 = 40;
+// frecency from history adds at most this much
+const MAX_RECENCY_BOOST: f64 = 150.0;
+const RECENCY_BOOST_PER_USE: f64 = 25.0;
+
+/// A search query prepared for scoring items of one page
+#[derive(Default, Clone)]
+pub struct Query {
+    text: String,
+    words: Vec<String>,
+    boosts: HashMap<String, f64>,
+}
+
+impl Query {
+    pub fn new(text: &str, kind: ItemKind) -> Self {
+        // `:heart_eyes:` searches like `heart_eyes`
+        let text = crate::shortcode::search_text(text).trim().to_lowercase();
+        let words = split_words(&text).map(str::to_string).collect();
+        let boosts = if text.is_empty() {
+            HashMap::new()
+        } else {
+            crate::history::get_frecencies(kind)
+        };
+        Self { text, words, boosts }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    /// Relevance of an item, None if it does not match at all.
+    /// `terms` are the item's lowercased names/keywords, `item` its text, which
+    /// matches too (typing "→" or pasting "😀") and gets the recency boost.
+    pub fn score(&self, item: &str, terms: &[String]) -> Option<u32> {
+        let own_text = item.to_lowercase();
+        let best = terms.iter()
+            .chain(std::iter::once(&own_text))
+            .filter_map(|term| self.score_term(term))
+            .max()
+            .or_else(|| self.score_words(terms))?;
+
+        let boost = self.boosts.get(item)
+            .map(|f| (f * RECENCY_BOOST_PER_USE).min(MAX_RECENCY_BOOST) as u32)
+            .unwrap_or(0);
+        Some(best + boost)
+    }
+
+    // whole query against a single term
+    fn score_term(&self, term: &str) -> Option<u32> {
+        let query = self.text.as_str();
+        let band = if term == query {
+            EXACT
+        } else if term.starts_with(query) {
+            PREFIX
+        } else if term.match_indices(query).any(|(i, _)| is_word_start(term, i)) {
+            WORD_START
+        } else if term.contains(query) {
+            CONTAINS
+        } else {
+            return None;
+        };
+        let extra = term.len().saturating_sub(query.len()).min(MAX_LENGTH_PENALTY);
+        Some(band - extra as u32)
+    }
+
+    // every query word on its own, anywhere in the terms, tolerating typos
+    fn score_words(&self, terms: &[String]) -> Option<u32> {
+        // only symbols in the query, nothing to match word by word
+        if self.words.is_empty() {
+            return None;
+        }
+        let term_words: Vec<&str> = terms.iter().flat_map(|t| split_words(t)).collect();
+        let mut typos = 0;
+        for word in &self.words {
+            if term_words.iter().any(|tw| tw.starts_with(word.as_str())) {
+                continue;
+            }
+            typos += term_words.iter().filter_map(|tw| typo_distance(word, tw)).min()?;
+        }
+
+        if typos == 0 {
+            Some(ALL_WORDS)
+        } else {
+            Some(FUZZY.saturating_sub(typos * TYPO_PENALTY).max(1))
+        }
+    }
+}
+
+/// Sorter putting the best scored items first while `query` is set.
+/// With an empty query everything compares equal and the store order is kept.
+pub fn score_sorter<T: IsA<glib::Object>>(query: Rc<RefCell<Query>>, score: fn(&T) -> u32) -> CustomSorter {
+    CustomSorter::new(move |a, b| {
+        if query.borrow().is_empty() {
+            return gtk4::Ordering::Equal;
+        }
+        let (Some(a), Some(b)) = (a.downcast_ref::<T>(), b.downcast_ref::<T>()) else {
+            return gtk4::Ordering::Equal;
+        };
+        score(b).cmp(&score(a)).into()
+    })
+}
+
+fn split_words(text: &str) -> impl Iterator<Item = &str> {
+    text.split(|c: char| !c.is_alphanumeric()).filter(|w| !w.is_empty())
+}
+
+fn is_word_start(text: &str, byte_index: usize) -> bool {
+    text[..byte_index].chars().next_back().is_none_or(|c| !c.is_alphanumeric())
+}
+
+// Edits needed to turn `word` into `candidate` (or the start of it), if
+// small enough to count as a typo. Short words are matched exactly only.
+fn typo_distance(word: &str, candidate: &str) -> Option<u32> {
+    let word: Vec<char> = word.chars().collect();
+    let max_edits = match word.len() {
+        0..=3 => return None,
+        4..=6 => 1,
+        _ => 2,
+    };
+
+    let candidate: Vec<char> = candidate.chars().collect();
+    // compare against the whole candidate and against prefixes of similar length
+    let prefix_lens = word.len() - 1..=word.len() + 1;
+    let distance = prefix_lens
+        .filter(|&len| len < candidate.len())
+        .map(|len| edit_distance(&word, &candidate[..len]))
+        .fold(edit_distance(&word, &candidate), u32::min);
+    (distance <= max_edits).then_some(distance)
+}
+
+// Damerau-Levenshtein (optimal string alignment) distance
+fn edit_distance(a: &[char], b: &[char]) -> u32 {
+    let mut rows = vec![vec![0u32; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i as u32;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j as u32;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = u32::from(a[i - 1] != b[j - 1]);
+            let mut best = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = best;
+        }
+    }
+    rows[a.len()][b.len()]
+}
@@ -0,0 +1,122 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::cell::RefCell;
+use crate::storage;
+
+const SETTINGS_FILE: &str = "settings.json";
+
+/// User preferences, missing fields fall back to their defaults
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct Settings {
+    // language of emoji names & keywords, None follows the session locale
+    pub locale: Option<String>,
+    // the All page also queries GIFs (network)
+    pub search_gifs_everywhere: bool,
+    // variant shown & inserted for emoji that have one, unless picked per emoji
+    pub skin_tone: SkinTone,
+    pub gender: Gender,
+    pub hair: Hair,
+    // hide emoji newer than this Emoji version ("13.0"), None shows all
+    pub max_emoji_version: Option<String>,
+    // how picked items reach other apps ("extension", "portal", ...), None detects one
+    pub insertion_backend: Option<String>,
+    // ms after pasting through the clipboard until its previous contents
+    // come back, None uses the default & 0 leaves the item there
+    pub clipboard_restore_delay: Option<u64>,
+    // lets the Remote Desktop portal start without asking again
+    pub portal_restore_token: Option<String>,
+}
+
+/// Preferred skin tone, `Default` is the yellow base emoji
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum SkinTone {
+    #[default]
+    Default,
+    Light,
+    MediumLight,
+    Medium,
+    MediumDark,
+    Dark,
+}
+
+/// Preferred gender for people emoji, `Neutral` keeps the gender-neutral base
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Gender {
+    #[default]
+    Neutral,
+    Woman,
+    Man,
+}
+
+/// Preferred hair style for person, woman & man
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Hair {
+    #[default]
+    Default,
+    Red,
+    Curly,
+    White,
+    Bald,
+}
+
+impl Settings {
+    fn get_path() -> PathBuf {
+        storage::data_file(SETTINGS_FILE)
+    }
+
+    pub fn load() -> Self {
+        storage::load_json(&Self::get_path()).unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        let path = Self::get_path();
+        match serde_json::to_vec_pretty(self) {
+            Ok(json) => {
+                if let Err(e) = storage::write_atomic(&path, &json) {
+                    eprintln!("Failed to save settings: {}", e);
+                }
+            }
+            Err(e) => eprintln!("Failed to serialize settings: {}", e),
+        }
+    }
+}
+
+// Global settings instance
+thread_local! {
+    static GLOBAL_SETTINGS: RefCell<Settings> = RefCell::new(Settings::load());
+    // Callbacks to notify UI when settings change
+    static SETTINGS_CALLBACKS: RefCell<Vec<Box<dyn Fn()>>> = RefCell::new(Vec::new());
+}
+
+pub fn get() -> Settings {
+    GLOBAL_SETTINGS.with(|s| s.borrow().clone())
+}
+
+/// Change settings, save them and notify listeners
+pub fn update(change: impl FnOnce(&mut Settings)) {
+    GLOBAL_SETTINGS.with(|s| {
+        let mut s = s.borrow_mut();
+        change(&mut s);
+        s.save();
+    });
+    notify_settings_changed();
+}
+
+/// Register a callback to be called when settings change
+pub fn on_settings_changed<F: Fn() + 'static>(callback: F) {
+    SETTINGS_CALLBACKS.with(|callbacks| {
+        callbacks.borrow_mut().push(Box::new(callback));
+    });
+}
+
+fn notify_settings_changed() {
+    SETTINGS_CALLBACKS.with(|callbacks| {
+        for callback in callbacks.borrow().iter() {
+            callback();
+        }
+    });
+}
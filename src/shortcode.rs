@@ -0,0 +1,41 @@
+use emojis::{Emoji, SkinTone};
+
+// Slack numbering, skin-tone-1 is the default yellow
+const SKIN_TONES: [SkinTone; 6] = [
+    SkinTone::Default,
+    SkinTone::Light,
+    SkinTone::MediumLight,
+    SkinTone::Medium,
+    SkinTone::MediumDark,
+    SkinTone::Dark,
+];
+
+/// Emoji for a complete Slack-style shortcode, `:tada:` or `:wave::skin-tone-3:`
+pub fn resolve(input: &str) -> Option<&'static Emoji> {
+    let inner = input.trim().strip_prefix(':')?.strip_suffix(':')?;
+    let (name, modifier) = match inner.split_once("::") {
+        Some((name, modifier)) => (name, Some(modifier)),
+        None => (inner, None),
+    };
+
+    let emoji = emojis::get_by_shortcode(name)?;
+    match modifier {
+        None => Some(emoji),
+        Some(modifier) => {
+            let tone: usize = modifier.strip_prefix("skin-tone-")?.parse().ok()?;
+            match *SKIN_TONES.get(tone.checked_sub(1)?)? {
+                SkinTone::Default => Some(emoji),
+                tone => emoji.with_skin_tone(tone),
+            }
+        }
+    }
+}
+
+/// Part of a (partial) shortcode worth searching for, `:thumbs` -> `thumbs`.
+/// Input without a leading `:` is returned unchanged.
+pub fn search_text(input: &str) -> &str {
+    match input.trim_start().strip_prefix(':') {
+        Some(rest) => rest.split("::").next().unwrap_or(rest).trim_end_matches(':'),
+        None => input,
+    }
+}
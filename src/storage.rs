@@ -0,0 +1,112 @@
+use serde::de::DeserializeOwned;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use gtk4::glib;
+
+/// Path of a file in Carmenta's data directory (~/.local/share/carmenta)
+pub fn data_file(name: &str) -> PathBuf {
+    let mut path = glib::user_data_dir(); // ~/.local/share
+    path.push("carmenta");
+    std::fs::create_dir_all(&path).ok();
+    path.push(name);
+    path
+}
+
+// sibling path with an extra suffix, e.g. history.json -> history.json.bak
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+pub fn backup_path(path: &Path) -> PathBuf {
+    with_suffix(path, ".bak")
+}
+
+/// Replace `path` with `contents` via temp file + rename, the previous version is kept as `.bak`
+pub fn write_atomic(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let tmp = with_suffix(path, ".tmp");
+    {
+        let mut file = fs::File::create(&tmp)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+    }
+
+    if path.exists() {
+        fs::rename(path, backup_path(path))?;
+    }
+    fs::rename(&tmp, path)
+}
+
+/// Advisory lock shared by every Carmenta instance touching `path`, released on drop
+pub fn lock(path: &Path, exclusive: bool) -> std::io::Result<fs::File> {
+    let file = fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(with_suffix(path, ".lock"))?;
+    if exclusive {
+        file.lock()?;
+    } else {
+        file.lock_shared()?;
+    }
+    Ok(file)
+}
+
+/// Read a JSON file, Ok(None) when it does not exist yet
+pub fn read_json<T: DeserializeOwned>(path: &Path) -> anyhow::Result<Option<T>> {
+    match fs::read_to_string(path) {
+        Ok(content) => Ok(Some(serde_json::from_str(&content)?)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+// tell the user in the window, a launch from a shortcut has no terminal
+fn report(message: String) {
+    eprintln!("{}", message);
+    glib::MainContext::default().invoke(move || crate::app::show_toast(&message));
+}
+
+/// Load a JSON file written by `write_atomic`, falling back to its `.bak` when
+/// the file is corrupted or a write got interrupted. A deleted file stays deleted.
+/// A corrupted file is reported and moved aside (`.corrupt`) so the next save
+/// neither overwrites it nor rotates it into the backup.
+pub fn load_json<T: DeserializeOwned>(path: &Path) -> Option<T> {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let corrupted = match read_json(path) {
+        Ok(Some(value)) => return Some(value),
+        // a leftover .tmp means a save stopped between its two renames
+        Ok(None) if !with_suffix(path, ".tmp").exists() => return None,
+        Ok(None) => false,
+        Err(e) => {
+            eprintln!("Failed to load {}: {}", path.display(), e);
+            let corrupt = with_suffix(path, ".corrupt");
+            match fs::rename(path, &corrupt) {
+                Ok(_) => eprintln!("Corrupted file kept as {}", corrupt.display()),
+                Err(e) => eprintln!("Failed to move corrupted file aside: {}", e),
+            }
+            true
+        }
+    };
+
+    let backup = backup_path(path);
+    match read_json(&backup) {
+        Ok(Some(value)) => {
+            let problem = if corrupted { "was damaged" } else { "was not saved completely" };
+            report(format!("{} {}, restored it from the backup", name, problem));
+            Some(value)
+        }
+        Ok(None) if corrupted => {
+            report(format!("{} was damaged and has no backup, kept it as {}.corrupt", name, name));
+            None
+        }
+        Ok(None) => None,
+        Err(e) => {
+            eprintln!("Failed to load backup {}: {}", backup.display(), e);
+            report(format!("{} could not be restored from its backup", name));
+            None
+        }
+    }
+}
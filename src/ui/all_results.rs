@@ -0,0 +1,365 @@
+use gtk4::prelude::*;
+use gtk4::{glib, Box, Button, CheckButton, FlowBox, Label, Orientation, PolicyType, ScrolledWindow, SelectionMode};
+use std::cell::{Cell, Ref, RefCell};
+use std::rc::Rc;
+use super::emoji_data::{self, EmojiObject, get_all_emojis};
+use super::kaomoji_data::{KaomojiObject, get_all_kaomojis};
+use super::symbols_data::{SymbolObject, get_symbols};
+use super::gif_data::{GifObject, search_gifs};
+use super::{compose, emoji_variants, gif_grid, item_menu, page, sidebar};
+use crate::history::ItemKind;
+use crate::search::Query;
+
+// top matches shown per section
+const MAX_EMOJI: usize = 24;
+const MAX_KAOMOJI: usize = 9;
+const MAX_SYMBOLS: usize = 16;
+const MAX_GIFS: usize = 6;
+
+// best `limit` matches of `items`, highest score first
+fn top_matches<T>(items: &[T], limit: usize, rank: impl Fn(&T) -> bool, score: impl Fn(&T) -> u32) -> Vec<&T> {
+    let mut matches: Vec<&T> = items.iter().filter(|item| rank(item)).collect();
+    matches.sort_by_key(|item| std::cmp::Reverse(score(item)));
+    matches.truncate(limit);
+    matches
+}
+
+/// Labelled block of result buttons, hidden while empty
+struct Section {
+    container: Box,
+    flow: FlowBox,
+}
+
+impl Section {
+    fn new(title: &str, per_line: u32) -> Self {
+        let container = Box::new(Orientation::Vertical, 6);
+        let label = Label::builder()
+            .label(title)
+            .css_classes(["heading"])
+            .xalign(0.0)
+            .margin_start(6)
+            .build();
+        let flow = FlowBox::builder()
+            .selection_mode(SelectionMode::None)
+            .homogeneous(true)
+            .max_children_per_line(per_line)
+            .min_children_per_line(per_line)
+            .build();
+        container.append(&label);
+        container.append(&flow);
+        container.set_visible(false);
+        Self { container, flow }
+    }
+
+    fn clear(&self) {
+        while let Some(child) = self.flow.first_child() {
+            self.flow.remove(&child);
+        }
+        self.container.set_visible(false);
+    }
+
+    fn append(&self, widget: &impl IsA<gtk4::Widget>) {
+        self.flow.insert(widget, -1);
+        self.container.set_visible(true);
+    }
+}
+
+fn item_button(kind: ItemKind, text: &str, tooltip: &str, css_class: &str) -> Button {
+    let button = Button::builder()
+        .label(text)
+        .tooltip_text(tooltip)
+        .css_classes([css_class, "flat"])
+        .build();
+    button.connect_clicked(move |btn| {
+        compose::pick(kind, btn.label().unwrap_or_default().to_string());
+    });
+    item_menu::attach_context_menu(&button);
+    button
+}
+
+fn gif_button(gif: &GifObject) -> Button {
+    let picture = gtk4::Picture::builder()
+        .width_request(100)
+        .height_request(100)
+        .build();
+    let button = Button::builder()
+        .child(&picture)
+        .tooltip_text(gif.title())
+        .css_classes(["gif-btn", "flat"])
+        .build();
+
+    button.connect_clicked(glib::clone!(#[strong] gif, move |_| gif_grid::insert_gif(&gif)));
+    // buttons are never reused, only rebuilt per query
+    gif_grid::load_preview(&picture, gif.preview_url(), gif.id(), |_| true);
+    button
+}
+
+// stop previews before their widgets go away
+fn stop_gifs(section: &Section) {
+    let mut child = section.flow.first_child();
+    while let Some(widget) = child {
+        let picture = widget.first_child()
+            .and_then(|button| button.first_child())
+            .and_downcast::<gtk4::Picture>();
+        if let Some(media) = picture.as_ref().and_then(|p| p.paintable()).and_downcast::<gtk4::MediaFile>() {
+            media.set_playing(false);
+        }
+        if let Some(picture) = picture {
+            picture.set_paintable(None::<&gtk4::gdk::Paintable>);
+        }
+        child = widget.next_sibling();
+    }
+}
+
+// items searched by the All page
+struct Catalog {
+    emojis: Vec<EmojiObject>,
+    kaomojis: Vec<KaomojiObject>,
+    symbols: Vec<SymbolObject>,
+}
+
+/// "Search everywhere": top emoji, kaomoji and symbol matches (and optionally GIFs) at once
+struct AllResults {
+    // loaded by the first query, not at startup with the other pages
+    catalog: RefCell<Option<Catalog>>,
+    emoji_section: Section,
+    kaomoji_section: Section,
+    symbol_section: Section,
+    gif_section: Section,
+    status: libadwaita::StatusPage,
+    results: ScrolledWindow,
+    // bumped per query so late GIF responses for older queries are dropped
+    generation: Cell<u64>,
+}
+
+impl AllResults {
+    fn catalog(&self) -> Ref<'_, Catalog> {
+        if self.catalog.borrow().is_none() {
+            *self.catalog.borrow_mut() = Some(Catalog {
+                emojis: get_all_emojis(),
+                kaomojis: get_all_kaomojis(),
+                symbols: get_symbols(),
+            });
+        }
+        Ref::map(self.catalog.borrow(), |catalog| catalog.as_ref().expect("catalog was just loaded"))
+    }
+
+    fn update(&self, text: &str) {
+        self.generation.set(self.generation.get() + 1);
+        stop_gifs(&self.gif_section);
+        for section in [&self.emoji_section, &self.kaomoji_section, &self.symbol_section, &self.gif_section] {
+            section.clear();
+        }
+
+        if text.trim().is_empty() {
+            self.show_status("Search Everywhere", "Type to find emoji, kaomoji and symbols at once");
+            return;
+        }
+
+        let catalog = self.catalog();
+        let query = Query::new(text, ItemKind::Emoji);
+        for e in top_matches(&catalog.emojis, MAX_EMOJI, |e| e.rank(&query), EmojiObject::search_score) {
+            let text = emoji_variants::preferred(&e.emoji());
+            self.emoji_section.append(&item_button(ItemKind::Emoji, &text, &e.name(), "emoji-btn"));
+        }
+        let query = Query::new(text, ItemKind::Kaomoji);
+        for k in top_matches(&catalog.kaomojis, MAX_KAOMOJI, |k| k.rank(&query), KaomojiObject::search_score) {
+            self.kaomoji_section.append(&item_button(ItemKind::Kaomoji, &k.text(), &k.name(), "kaomoji-btn"));
+        }
+        let query = Query::new(text, ItemKind::Symbol);
+        for s in top_matches(&catalog.symbols, MAX_SYMBOLS, |s| s.rank(&query), SymbolObject::search_score) {
+            self.symbol_section.append(&item_button(ItemKind::Symbol, &s.char(), &s.name(), "emoji-btn"));
+        }
+
+        let found = [&self.emoji_section, &self.kaomoji_section, &self.symbol_section]
+            .iter()
+            .any(|s| s.container.is_visible());
+        if found {
+            self.status.set_visible(false);
+            self.results.set_visible(true);
+        } else {
+            self.show_status("No Results", "Try a different search");
+        }
+    }
+
+    // Enter in the search entry takes the best match of the first non-empty section
+    fn activate_first(&self) {
+        let sections = [&self.emoji_section, &self.kaomoji_section, &self.symbol_section, &self.gif_section];
+        let first = sections.iter()
+            .filter(|s| s.container.is_visible())
+            .find_map(|s| s.flow.child_at_index(0))
+            .and_then(|child| child.child())
+            .and_downcast::<Button>();
+        if let Some(button) = first {
+            button.emit_clicked();
+        }
+    }
+
+    fn show_status(&self, title: &str, description: &str) {
+        self.status.set_title(title);
+        self.status.set_description(Some(description));
+        self.status.set_visible(true);
+        self.results.set_visible(false);
+    }
+
+    fn show_gifs(&self, gifs: Vec<GifObject>) {
+        for gif in gifs.iter().take(MAX_GIFS) {
+            self.gif_section.append(&gif_button(gif));
+        }
+        if !gifs.is_empty() {
+            self.status.set_visible(false);
+            self.results.set_visible(true);
+        }
+    }
+}
+
+pub fn create_all_results(search_entry: &gtk4::SearchEntry) -> (Box, page::Page) {
+    let container = Box::new(Orientation::Vertical, 0);
+    container.set_css_classes(&["all-page"]);
+
+    let gifs_check = CheckButton::builder()
+        .label("Include GIFs")
+        .active(crate::settings::get().search_gifs_everywhere)
+        .halign(gtk4::Align::End)
+        .margin_end(12)
+        .build();
+    container.append(&gifs_check);
+
+    let status = libadwaita::StatusPage::builder()
+        .icon_name("edit-find-symbolic")
+        .vexpand(true)
+        .build();
+    status.add_css_class("compact");
+
+    let emoji_section = Section::new("Emoji", 8);
+    let kaomoji_section = Section::new("Kaomoji", 3);
+    let symbol_section = Section::new("Symbols", 8);
+    let gif_section = Section::new("GIFs", 3);
+
+    let sections = Box::new(Orientation::Vertical, 12);
+    sections.set_margin_start(6);
+    sections.set_margin_end(6);
+    sections.set_margin_bottom(6);
+    for section in [&emoji_section, &kaomoji_section, &symbol_section, &gif_section] {
+        sections.append(&section.container);
+    }
+
+    let results = ScrolledWindow::builder()
+        .hscrollbar_policy(PolicyType::Never)
+        .vscrollbar_policy(PolicyType::Automatic)
+        .child(&sections)
+        .hexpand(true)
+        .vexpand(true)
+        .build();
+
+    container.append(&status);
+    container.append(&results);
+
+    let all = Rc::new(AllResults {
+        catalog: RefCell::new(None),
+        emoji_section,
+        kaomoji_section,
+        symbol_section,
+        gif_section,
+        status,
+        results,
+        generation: Cell::new(0),
+    });
+    all.update("");
+
+    // Run the query against every page, GIFs last as they come from the network
+    let run_query = glib::clone!(#[strong] all, move |text: String| {
+        all.update(&text);
+        if text.trim().is_empty() || !crate::settings::get().search_gifs_everywhere {
+            return;
+        }
+
+        let generation = all.generation.get();
+        let all_weak = Rc::downgrade(&all);
+        gif_grid::spawn_tokio(
+            async move { search_gifs(&text).await },
+            move |results| {
+                let Some(all) = all_weak.upgrade() else { return };
+                if all.generation.get() != generation {
+                    return; // query changed meanwhile
+                }
+                match results {
+                    Ok(gifs) => all.show_gifs(gifs.into_iter().map(GifObject::from_data).collect()),
+                    Err(e) => eprintln!("GIF search error: {}", e),
+                }
+            }
+        );
+    });
+    let run_query = Rc::new(run_query);
+
+    // Ranking every catalog (and asking Klipy) is wasted while the page is hidden,
+    // the last query typed meanwhile runs when it's shown
+    let pending: Rc<RefCell<Option<String>>> = Rc::new(RefCell::new(None));
+    container.connect_map(glib::clone!(#[strong] run_query, #[strong] pending, move |_| {
+        if let Some(text) = pending.take() {
+            run_query(text);
+        }
+    }));
+
+    // Connect Search with debounce (150ms)
+    let debounce_source: Rc<RefCell<Option<glib::SourceId>>> = Rc::new(RefCell::new(None));
+    search_entry.connect_search_changed(glib::clone!(#[strong] run_query, #[strong] debounce_source, #[strong] pending, #[weak] container, move |entry| {
+        // Cancel previous debounce timer if still pending
+        if let Some(source_id) = debounce_source.borrow_mut().take() {
+            let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                source_id.remove();
+            }));
+        }
+
+        let text = entry.text().to_string();
+        if !container.is_mapped() {
+            *pending.borrow_mut() = Some(text);
+            return;
+        }
+        let run_query = run_query.clone();
+        let debounce_source_clone = debounce_source.clone();
+
+        let source_id = glib::timeout_add_local_once(
+            std::time::Duration::from_millis(150),
+            move || {
+                *debounce_source_clone.borrow_mut() = None;
+                run_query(text);
+            }
+        );
+        *debounce_source.borrow_mut() = Some(source_id);
+    }));
+
+    gifs_check.connect_toggled(glib::clone!(#[strong] run_query, #[weak] search_entry, move |check| {
+        let enabled = check.is_active();
+        crate::settings::update(|s| s.search_gifs_everywhere = enabled);
+        run_query(search_entry.text().to_string());
+    }));
+
+    // Emoji names follow the emoji language, the version cap hides some
+    let loaded = RefCell::new(emoji_data::catalog_settings());
+    let all_weak = Rc::downgrade(&all);
+    let search_weak = search_entry.downgrade();
+    let container_weak = container.downgrade();
+    crate::settings::on_settings_changed(move || {
+        let catalog = emoji_data::catalog_settings();
+        if loaded.replace(catalog.clone()) == catalog {
+            return;
+        }
+        if let (Some(all), Some(entry)) = (all_weak.upgrade(), search_weak.upgrade()) {
+            all.catalog.borrow_mut().take();
+            if container_weak.upgrade().is_some_and(|c| c.is_mapped()) {
+                all.update(&entry.text());
+            } else {
+                *pending.borrow_mut() = Some(entry.text().to_string());
+            }
+        }
+    });
+
+    let all_weak = Rc::downgrade(&all);
+    let page = page::Page::new(sidebar::Categories::default(), &all.results, move || {
+        if let Some(all) = all_weak.upgrade() {
+            all.activate_first();
+        }
+    });
+    (container, page)
+}
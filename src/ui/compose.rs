@@ -0,0 +1,141 @@
+use gtk4::prelude::*;
+use gtk4::{glib, Box, Button, Entry, Orientation, Revealer, RevealerTransitionType, ToggleButton};
+use std::cell::{Cell, RefCell};
+use crate::history::ItemKind;
+
+/// Buffer of picked items shown above the pages, inserted as one string
+struct ComposeBar {
+    revealer: Revealer,
+    entry: Entry,
+    toggle: ToggleButton,
+    // what got picked, recorded in history once inserted
+    pieces: RefCell<Vec<(ItemKind, String)>>,
+}
+
+thread_local! {
+    static COMPOSE_BAR: RefCell<Option<ComposeBar>> = const { RefCell::new(None) };
+    // Shift+click appends to the buffer even outside compose mode
+    static SHIFT_HELD: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Build the buffer bar, `toggle` switches compose mode on and off
+pub fn create_compose_bar(toggle: &ToggleButton) -> Revealer {
+    let bar = Box::new(Orientation::Horizontal, 6);
+    bar.set_margin_start(12);
+    bar.set_margin_end(12);
+    bar.set_margin_bottom(6);
+
+    let entry = Entry::builder()
+        .placeholder_text("Pick items to compose…")
+        .hexpand(true)
+        .build();
+    let clear_btn = Button::builder()
+        .icon_name("edit-clear-symbolic")
+        .tooltip_text("Clear")
+        .build();
+    let insert_btn = Button::builder()
+        .label("Insert")
+        .css_classes(["suggested-action"])
+        .build();
+    bar.append(&entry);
+    bar.append(&clear_btn);
+    bar.append(&insert_btn);
+
+    let revealer = Revealer::builder()
+        .transition_type(RevealerTransitionType::SlideDown)
+        .child(&bar)
+        .reveal_child(toggle.is_active())
+        .build();
+
+    entry.connect_activate(|_| insert());
+    entry.connect_changed(|_| update_visibility());
+    insert_btn.connect_clicked(|_| insert());
+    clear_btn.connect_clicked(|_| clear());
+    toggle.connect_toggled(|_| update_visibility());
+
+    COMPOSE_BAR.with(|c| {
+        *c.borrow_mut() = Some(ComposeBar {
+            revealer: revealer.clone(),
+            entry,
+            toggle: toggle.clone(),
+            pieces: RefCell::new(Vec::new()),
+        });
+    });
+    revealer
+}
+
+pub fn set_shift_held(held: bool) {
+    SHIFT_HELD.with(|s| s.set(held));
+}
+
+fn is_composing() -> bool {
+    COMPOSE_BAR.with(|c| c.borrow().as_ref().is_some_and(|bar| bar.toggle.is_active()))
+}
+
+/// An item got picked: append it to the buffer in compose mode (or with Shift held),
+/// otherwise insert it right away
+pub fn pick(kind: ItemKind, text: String) {
+    if is_composing() || SHIFT_HELD.with(|s| s.get()) {
+        append(kind, text);
+        return;
+    }
+
+    crate::app::mark_inserting();
+    crate::history::add_recent(kind, text.clone());
+    crate::insertion::insert_or_copy(&text);
+}
+
+fn append(kind: ItemKind, text: String) {
+    COMPOSE_BAR.with(|c| {
+        let c = c.borrow();
+        let Some(bar) = c.as_ref() else { return };
+        let mut position = i32::from(bar.entry.text_length());
+        bar.entry.insert_text(&text, &mut position);
+        bar.entry.set_position(-1);
+        bar.pieces.borrow_mut().push((kind, text));
+    });
+}
+
+// the bar stays visible while it holds something, even outside compose mode
+fn update_visibility() {
+    COMPOSE_BAR.with(|c| {
+        if let Some(bar) = c.borrow().as_ref() {
+            bar.revealer.set_reveal_child(bar.toggle.is_active() || !bar.entry.text().is_empty());
+        }
+    });
+}
+
+/// Empty the buffer, compose mode stays as it is
+pub fn clear() {
+    COMPOSE_BAR.with(|c| {
+        if let Some(bar) = c.borrow().as_ref() {
+            bar.entry.set_text("");
+            bar.pieces.borrow_mut().clear();
+        }
+    });
+    update_visibility();
+}
+
+/// Insert the whole buffer with a single call, every picked piece that
+/// survived editing goes to history
+fn insert() {
+    let Some((text, pieces)) = COMPOSE_BAR.with(|c| {
+        c.borrow().as_ref().map(|bar| (bar.entry.text().to_string(), bar.pieces.borrow_mut().split_off(0)))
+    }) else {
+        return;
+    };
+    if text.is_empty() {
+        return;
+    }
+
+    for (kind, piece) in pieces {
+        if text.contains(&piece) {
+            crate::history::add_recent(kind, piece);
+        }
+    }
+    crate::app::mark_inserting();
+    crate::insertion::insert_or_copy(&text);
+
+    // defer, the entry may still be in its activate handler
+    glib::idle_add_local_once(clear);
+}
@@ -0,0 +1,88 @@
+use gtk4::prelude::*;
+use libadwaita::prelude::*;
+use gtk4::glib;
+use libadwaita::{ActionRow, PreferencesGroup, PreferencesPage};
+use std::cell::{Cell, RefCell};
+use crate::dbus::{self, DBusClient, ExtensionStatus};
+
+thread_local! {
+    // refreshes the open dialog, if any
+    static REFRESH: RefCell<Option<Box<dyn Fn()>>> = const { RefCell::new(None) };
+    // the status callback is registered once and outlives every dialog
+    static WATCHING: Cell<bool> = const { Cell::new(false) };
+}
+
+fn refresh() {
+    REFRESH.with(|r| {
+        if let Some(refresh) = r.borrow().as_ref() {
+            refresh();
+        }
+    });
+}
+
+fn info_row(title: &str) -> ActionRow {
+    ActionRow::builder()
+        .title(title)
+        .subtitle_selectable(true)
+        .build()
+}
+
+/// Dialog with the D-Bus connection, extension handshake and last insertion error
+pub fn show_diagnostics(parent: &gtk4::Window) {
+    let connection_row = info_row("Session Bus");
+    let extension_row = info_row("Shell Extension");
+    let capabilities_row = info_row("Capabilities");
+    let method_row = info_row("Insertion Method");
+    let backend_row = info_row("Last Insertion");
+    let error_row = info_row("Last Error");
+
+    let check_btn = gtk4::Button::builder()
+        .label("Check Again")
+        .valign(gtk4::Align::Center)
+        .css_classes(["flat"])
+        .build();
+    check_btn.connect_clicked(|_| DBusClient::init_connection());
+
+    let group = PreferencesGroup::builder()
+        .title("Insertion")
+        .header_suffix(&check_btn)
+        .build();
+    for row in [&connection_row, &extension_row, &capabilities_row, &method_row, &backend_row, &error_row] {
+        group.add(row);
+    }
+    let page = PreferencesPage::new();
+    page.add(&group);
+
+    let toolbar = libadwaita::ToolbarView::new();
+    toolbar.add_top_bar(&libadwaita::HeaderBar::new());
+    toolbar.set_content(Some(&page));
+
+    let dialog = libadwaita::Dialog::builder()
+        .title("Diagnostics")
+        .content_width(380)
+        .child(&toolbar)
+        .build();
+
+    let update = glib::clone!(#[weak] connection_row, #[weak] extension_row, #[weak] capabilities_row, #[weak] method_row, #[weak] backend_row, #[weak] error_row, move || {
+        let state = dbus::diagnostics();
+        connection_row.set_subtitle(if state.connected { "Connected" } else { "Not connected" });
+        extension_row.set_subtitle(&state.extension.summary());
+        let capabilities = match &state.extension {
+            ExtensionStatus::Working { capabilities, .. } if !capabilities.is_empty() => capabilities.join(", "),
+            _ => "—".to_string(),
+        };
+        capabilities_row.set_subtitle(&capabilities);
+        let method = crate::settings::get().insertion_backend;
+        method_row.set_subtitle(method.as_deref().unwrap_or("Automatic"));
+        backend_row.set_subtitle(state.last_backend.unwrap_or("Nothing inserted yet"));
+        error_row.set_subtitle(state.last_error.as_deref().unwrap_or("None"));
+    });
+    update();
+    REFRESH.with(|r| *r.borrow_mut() = Some(Box::new(update)));
+    if !WATCHING.with(|w| w.replace(true)) {
+        dbus::on_status_changed(refresh);
+    }
+    dialog.connect_closed(|_| REFRESH.with(|r| *r.borrow_mut() = None));
+
+    dialog.present(Some(parent));
+}
@@ -0,0 +1,81 @@
+use gtk4::glib;
+
+// (emoji, name, keywords)
+type Annotation = (&'static str, &'static str, &'static [&'static str]);
+
+// CLDR names & keywords per locale and Unicode subgroups, bundled at build time (see build.rs)
+include!(concat!(env!("OUT_DIR"), "/emoji_annotations.rs"));
+
+/// Languages with bundled emoji names, (locale, label)
+pub const LANGUAGES: &[(&str, &str)] = &[("en", "English"), ("pl", "Polski"), ("de", "Deutsch")];
+pub const FALLBACK_LOCALE: &str = "en";
+
+// tables are keyed without variation selectors and sorted by key
+fn lookup<'a, T>(table: &'a [T], emoji: &str, key: impl Fn(&T) -> &str) -> Option<&'a T> {
+    let emoji = emoji.replace('\u{fe0f}', "");
+    table.binary_search_by(|entry| key(entry).cmp(emoji.as_str()))
+        .ok()
+        .map(|i| &table[i])
+}
+
+fn annotation(locale: &str, emoji: &str) -> Option<&'static Annotation> {
+    let (_, table) = ANNOTATIONS.iter().find(|(l, _)| *l == locale)?;
+    lookup(table, emoji, |(e, _, _)| e)
+}
+
+/// Locale used for emoji names: the override from settings, else the first
+/// session language we have data for, else English
+pub fn locale() -> &'static str {
+    let wanted = crate::settings::get().locale;
+    let session = glib::language_names();
+    // "pl_PL.UTF-8" -> "pl"
+    let candidates = wanted.iter().map(String::as_str)
+        .chain(session.iter().map(|l| l.as_str()))
+        .map(|l| l.split(['_', '.', '@']).next().unwrap_or(l));
+
+    for candidate in candidates {
+        if let Some((locale, _)) = LANGUAGES.iter().find(|(l, _)| *l == candidate) {
+            return locale;
+        }
+    }
+    FALLBACK_LOCALE
+}
+
+/// Localized CLDR name, e.g. "czerwone serce"
+pub fn name(locale: &str, emoji: &str) -> Option<&'static str> {
+    annotation(locale, emoji).map(|(_, name, _)| *name).filter(|n| !n.is_empty())
+}
+
+/// Localized CLDR keywords (lowercase)
+pub fn keywords(locale: &str, emoji: &str) -> &'static [&'static str] {
+    annotation(locale, emoji).map(|(_, _, keywords)| *keywords).unwrap_or(&[])
+}
+
+/// Unicode subgroup, e.g. "face smiling"
+pub fn subgroup(emoji: &str) -> Option<&'static str> {
+    lookup(SUBGROUPS, emoji, |(e, _)| e).map(|(_, subgroup)| *subgroup)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundled_locales_have_names() {
+        assert_eq!(name("en", "❤️"), Some("red heart"));
+        assert_eq!(name("pl", "❤️"), Some("czerwone serce"));
+        assert_eq!(name("de", "❤️"), Some("rotes Herz"));
+        assert_eq!(name("de", "😀"), Some("grinsendes Gesicht"));
+    }
+
+    #[test]
+    fn lookup_ignores_variation_selectors() {
+        assert_eq!(name("pl", "❤"), name("pl", "❤️"));
+        assert!(keywords("de", "❤️").contains(&"herz"));
+    }
+
+    #[test]
+    fn unknown_locale_has_no_names() {
+        assert_eq!(name("xx", "❤️"), None);
+    }
+}
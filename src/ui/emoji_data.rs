@@ -1,9 +1,11 @@
 use gtk4::glib;
 use gtk4::subclass::prelude::*;
+use crate::search::Query;
+use super::{emoji_annotations, font_coverage};
 
 pub mod imp {
     use super::*;
-    use std::cell::RefCell;
+    use std::cell::{Cell, RefCell};
 
     #[derive(Default, Clone, Copy, PartialEq, Eq, Debug)]
     pub enum EmojiCategory {
@@ -17,6 +19,7 @@ pub mod imp {
         Symbols,
         Flags,
         Recent, // Special
+        Favorites, // Special
     }
 
     #[derive(Default)]
@@ -27,6 +30,7 @@ pub mod imp {
         pub category: RefCell<EmojiCategory>,
         pub keywords: RefCell<Vec<String>>,
         pub keywords_lower: RefCell<Vec<String>>, // Cached
+        pub search_score: Cell<u32>, // set by the search filter
     }
 
     #[glib::object_subclass]
@@ -59,6 +63,28 @@ impl EmojiObject {
         obj
     }
 
+    /// Object for an `emojis` entry named in `locale`, indexed by the localized
+    /// and English names & CLDR keywords, every shortcode alias and its subgroup
+    pub fn from_emoji(emoji: &emojis::Emoji, category: imp::EmojiCategory, locale: &str) -> Self {
+        let text = emoji.as_str();
+        let english_name = emoji.name().to_string();
+        let name = emoji_annotations::name(locale, text)
+            .map(str::to_string)
+            .unwrap_or_else(|| english_name.clone());
+
+        let mut keywords = vec![name.clone(), english_name];
+        keywords.extend(emoji.shortcodes().map(str::to_string));
+        keywords.extend(emoji_annotations::subgroup(text).map(str::to_string));
+        keywords.extend(emoji_annotations::keywords(locale, text).iter().map(|k| k.to_string()));
+        if locale != emoji_annotations::FALLBACK_LOCALE {
+            keywords.extend(emoji_annotations::keywords(emoji_annotations::FALLBACK_LOCALE, text).iter().map(|k| k.to_string()));
+        }
+        let mut seen = std::collections::HashSet::new();
+        keywords.retain(|k| seen.insert(k.to_lowercase()));
+
+        Self::new(text.to_string(), name, category, keywords)
+    }
+
     pub fn emoji(&self) -> String {
         self.imp().data.borrow().clone()
     }
@@ -84,15 +110,52 @@ impl EmojiObject {
     pub fn keywords_lower(&self) -> Vec<String> {
         self.imp().keywords_lower.borrow().clone()
     }
+
+    /// Score against a search query, kept for sorting. Returns whether it matched.
+    pub fn rank(&self, query: &Query) -> bool {
+        let score = query.score(&self.imp().data.borrow(), &self.imp().keywords_lower.borrow());
+        self.imp().search_score.set(score.unwrap_or(0));
+        score.is_some()
+    }
+
+    pub fn search_score(&self) -> u32 {
+        self.imp().search_score.get()
+    }
 }
 
 pub use imp::EmojiCategory;
 
+/// Emoji versions offered as a cap, newest first
+pub const EMOJI_VERSIONS: &[&str] = &["17.0", "16.0", "15.1", "15.0", "14.0", "13.1", "13.0", "12.1", "12.0", "11.0"];
+
+// "13.1" -> 13.1
+fn parse_version(version: &str) -> Option<emojis::UnicodeVersion> {
+    let (major, minor) = version.split_once('.').unwrap_or((version, "0"));
+    Some(emojis::UnicodeVersion::new(major.parse().ok()?, minor.parse().ok()?))
+}
+
+/// Filter for the emoji Carmenta offers: not newer than the configured
+/// Emoji version and drawable with the installed fonts (no tofu boxes)
+pub fn availability() -> impl Fn(&emojis::Emoji) -> bool {
+    let max_version = crate::settings::get().max_emoji_version.as_deref().and_then(parse_version);
+    move |emoji| {
+        max_version.is_none_or(|max| emoji.unicode_version() <= max)
+            && font_coverage::can_render(emoji.as_str())
+    }
+}
+
+/// Settings `get_all_emojis` depends on, reload its items when they change
+pub fn catalog_settings() -> (&'static str, Option<String>) {
+    (emoji_annotations::locale(), crate::settings::get().max_emoji_version)
+}
+
 pub fn get_all_emojis() -> Vec<EmojiObject> {
     use emojis::Group;
     let mut grid_items = Vec::new(); 
+    let locale = emoji_annotations::locale();
+    let available = availability();
     
-    for emoji in emojis::iter() {
+    for emoji in emojis::iter().filter(|e| available(e)) {
         let group = emoji.group();
         let category = match group {
             Group::SmileysAndEmotion => EmojiCategory::SmileysAndPeople,
@@ -106,18 +169,7 @@ pub fn get_all_emojis() -> Vec<EmojiObject> {
             Group::Flags => EmojiCategory::Flags,
         };
 
-        let name = emoji.name().to_string();
-        let mut keywords = vec![name.clone()];
-        if let Some(short) = emoji.shortcode() {
-            keywords.push(short.to_string());
-        }
-
-        grid_items.push(EmojiObject::new(
-            emoji.as_str().to_string(),
-            name,
-            category,
-            keywords
-        ));
+        grid_items.push(EmojiObject::from_emoji(emoji, category, locale));
     }
     grid_items
 }
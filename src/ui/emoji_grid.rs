@@ -2,22 +2,87 @@ use gtk4::prelude::*;
 use gtk4::{
     gio, glib, GridView, SignalListItemFactory, SingleSelection, 
     PolicyType, ScrolledWindow, Box, Orientation, ToggleButton, 
-    CustomFilter, FilterListModel, Popover
+    CustomFilter, FilterListModel, SortListModel
 };
-use super::emoji_data::{EmojiCategory, EmojiObject, get_all_emojis};
-use crate::dbus::DBusClient;
+use super::emoji_data::{self, EmojiCategory, EmojiObject, get_all_emojis};
+use super::{compose, emoji_annotations, emoji_variants, item_menu, page, sidebar};
+use crate::history::ItemKind;
+use crate::search::{self, Query};
 use std::cell::RefCell;
 use std::rc::Rc;
 
-// helper function: Insert text & manage history/focus
-fn insert_helper(text: String) {
-     crate::app::mark_inserting();
-     crate::history::add_recent(text.clone());
-     
-     DBusClient::insert_or_copy(&text);
+// Recent & Favorites hold copies of regular items
+fn is_special(category: EmojiCategory) -> bool {
+    matches!(category, EmojiCategory::Recent | EmojiCategory::Favorites)
 }
 
-pub fn create_emoji_grid(search_entry: &gtk4::SearchEntry) -> Box {
+// Recent shows exactly what was inserted, everything else the preferred variant
+fn display_text(obj: &EmojiObject) -> String {
+    if obj.category() == EmojiCategory::Recent {
+        obj.emoji()
+    } else {
+        emoji_variants::preferred(&obj.emoji())
+    }
+}
+
+// Separate skin tone for each of the two people (or hands) of 🤝, 💑 & co.
+fn pair_tone_picker(emoji: &'static emojis::Emoji, pick: Rc<dyn Fn(String)>) -> Box {
+    let picker = Box::new(Orientation::Vertical, 5);
+    let start = emoji_variants::preferred_tone_index().unwrap_or(0);
+    let tones = Rc::new(std::cell::Cell::new((start, start)));
+
+    let preview = gtk4::Button::builder()
+        .css_classes(["emoji-btn", "flat"])
+        .tooltip_text("Insert this combination")
+        .build();
+    // only sequences Unicode defines can be inserted
+    let update_preview = glib::clone!(#[weak] preview, #[strong] tones, move || {
+        let (first, second) = tones.get();
+        let combined = emoji_variants::with_pair_tones(emoji, first, second);
+        preview.set_label(combined.unwrap_or(emoji).as_str());
+        preview.set_sensitive(combined.is_some());
+    });
+    update_preview();
+    preview.connect_clicked(move |btn| pick(btn.label().unwrap_or_default().to_string()));
+
+    let rows = Box::new(Orientation::Vertical, 5);
+    for (person, title) in ["Left", "Right"].into_iter().enumerate() {
+        let row = Box::new(Orientation::Horizontal, 5);
+        row.append(&gtk4::Label::builder().label(title).width_chars(5).xalign(0.0).build());
+
+        let mut group: Option<ToggleButton> = None;
+        for (tone, modifier) in emoji_variants::TONE_MODIFIERS.into_iter().enumerate() {
+            let tone_btn = ToggleButton::builder()
+                .label(modifier)
+                .css_classes(["emoji-btn-small", "flat"])
+                .active(tone == start)
+                .build();
+            tone_btn.set_group(group.as_ref());
+            group.get_or_insert(tone_btn.clone());
+
+            let update_preview = update_preview.clone();
+            tone_btn.connect_toggled(glib::clone!(#[strong] tones, move |b| {
+                if !b.is_active() {
+                    return;
+                }
+                let (first, second) = tones.get();
+                tones.set(if person == 0 { (tone, second) } else { (first, tone) });
+                update_preview();
+            }));
+            row.append(&tone_btn);
+        }
+        rows.append(&row);
+    }
+
+    let layout = Box::new(Orientation::Horizontal, 10);
+    layout.append(&rows);
+    layout.append(&preview);
+    picker.append(&gtk4::Separator::new(Orientation::Horizontal));
+    picker.append(&layout);
+    picker
+}
+
+pub fn create_emoji_grid(search_entry: &gtk4::SearchEntry) -> (Box, page::Page) {
     // Top container: Categories + Grid
     let container = Box::new(Orientation::Horizontal, 0);
     container.set_css_classes(&["emoji-page"]);
@@ -36,13 +101,13 @@ pub fn create_emoji_grid(search_entry: &gtk4::SearchEntry) -> Box {
     let all_emojis = get_all_emojis();
     store.extend_from_slice(&all_emojis);
     
-    // helper function to rebuild Recent items in store
-    fn rebuild_recent(store: &gio::ListStore) {
-        // Remove existing Recent items (at the beginning)
+    // helper function to rebuild Recent & Favorites items in store
+    fn rebuild_special(store: &gio::ListStore) {
+        // Remove existing special items (at the beginning)
         while store.n_items() > 0 {
             if let Some(obj) = store.item(0) {
                 if let Some(emoji_obj) = obj.downcast_ref::<EmojiObject>() {
-                    if emoji_obj.category() == EmojiCategory::Recent {
+                    if is_special(emoji_obj.category()) {
                         store.remove(0);
                         continue;
                     }
@@ -51,39 +116,63 @@ pub fn create_emoji_grid(search_entry: &gtk4::SearchEntry) -> Box {
             break;
         }
         
-        // Add current Recent items at the beginning
-        let recent = crate::history::get_recent();
-        for (i, r) in recent.iter().enumerate() {
-            if let Some(e) = emojis::get(r) {
-                let name = e.name().to_string();
-                let mut keywords = vec![name.clone()];
-                if let Some(short) = e.shortcode() {
-                    keywords.push(short.to_string());
-                }
-                store.insert(i as u32, &EmojiObject::new(
-                    r.clone(), 
-                    name, 
-                    EmojiCategory::Recent,
-                    keywords
-                ));
+        // Add current Recent items (ranked by frecency), then Favorites, at the beginning
+        let special = crate::history::get_recent(ItemKind::Emoji).into_iter()
+            .map(|r| (r, EmojiCategory::Recent))
+            .chain(crate::favorites::get_favorites().into_iter().map(|f| (f, EmojiCategory::Favorites)));
+
+        let locale = emoji_annotations::locale();
+        let available = emoji_data::availability();
+        let mut pos = 0;
+        for (text, category) in special {
+            if let Some(e) = emojis::get(&text).filter(|e| available(e)) {
+                store.insert(pos, &EmojiObject::from_emoji(e, category, locale));
+                pos += 1;
             }
         }
     }
     
-    // Initial population of Recent
-    rebuild_recent(&store);
+    // Initial population of Recent & Favorites
+    rebuild_special(&store);
     
-    // Register callback to refresh Recent when history changes
+    // Register callbacks to refresh special categories when history/favorites change
     let store_weak = store.downgrade();
     crate::history::on_history_changed(move || {
         if let Some(store) = store_weak.upgrade() {
-            rebuild_recent(&store);
+            rebuild_special(&store);
+        }
+    });
+    let store_weak = store.downgrade();
+    crate::favorites::on_favorites_changed(move || {
+        if let Some(store) = store_weak.upgrade() {
+            rebuild_special(&store);
+        }
+    });
+
+    // Names & keywords are baked into the items, reload them when the emoji language
+    // or version cap changes; a new preferred variant only needs the cells bound again
+    let store_weak = store.downgrade();
+    let loaded = RefCell::new(emoji_data::catalog_settings());
+    let variant_prefs = |s: &crate::settings::Settings| (s.skin_tone, s.gender, s.hair);
+    let shown_prefs = std::cell::Cell::new(variant_prefs(&crate::settings::get()));
+    crate::settings::on_settings_changed(move || {
+        let Some(store) = store_weak.upgrade() else { return };
+        let catalog = emoji_data::catalog_settings();
+        let prefs = variant_prefs(&crate::settings::get());
+        if loaded.replace(catalog.clone()) != catalog {
+            store.remove_all();
+            store.extend_from_slice(&get_all_emojis());
+            rebuild_special(&store);
+        } else if shown_prefs.get() != prefs {
+            let n = store.n_items();
+            store.items_changed(0, n, n);
         }
+        shown_prefs.set(prefs);
     });
 
     // Filter Logic
     let current_category = Rc::new(RefCell::new(EmojiCategory::SmileysAndPeople));
-    let current_query = Rc::new(RefCell::new(String::new()));
+    let current_query = Rc::new(RefCell::new(Query::default()));
 
     let filter = CustomFilter::new(glib::clone!(#[strong] current_category, #[strong] current_query, move |obj| {
         let emoji_obj = obj.downcast_ref::<EmojiObject>().unwrap();
@@ -91,30 +180,28 @@ pub fn create_emoji_grid(search_entry: &gtk4::SearchEntry) -> Box {
         
         // 1. Search filter
         if !query.is_empty() {
-            // skip Recent category during search to avoid duplicates
-            if emoji_obj.category() == EmojiCategory::Recent {
+            // skip Recent & Favorites during search to avoid duplicates
+            if is_special(emoji_obj.category()) {
                 return false;
             }
             
-            let keywords = emoji_obj.keywords_lower();
-            for k in keywords {
-                if k.contains(query.as_str()) {
-                    return true;
-                }
-            }
-            return false;
+            return emoji_obj.rank(&query);
         }
 
         // 2. Category filter
         emoji_obj.category() == *current_category.borrow()
     }));
 
+    // best matches first while searching
+    let sorter = search::score_sorter(current_query.clone(), EmojiObject::search_score);
+
     let filter_model = FilterListModel::new(Some(store), Some(filter.clone()));
-    let selection_model = SingleSelection::new(Some(filter_model));
+    let sort_model = SortListModel::new(Some(filter_model), Some(sorter.clone()));
+    let selection_model = SingleSelection::new(Some(sort_model));
 
     // Connect Search Entry with debounce (150ms)
     let debounce_source: Rc<RefCell<Option<glib::SourceId>>> = Rc::new(RefCell::new(None));
-    search_entry.connect_search_changed(glib::clone!(#[weak] filter, #[strong] current_query, #[strong] debounce_source, move |entry| {
+    search_entry.connect_search_changed(glib::clone!(#[weak] filter, #[weak] sorter, #[weak] selection_model, #[strong] current_query, #[strong] debounce_source, move |entry| {
         // Cancel previous debounce timer if still pending
         if let Some(source_id) = debounce_source.borrow_mut().take() {
             // Use try pattern - source may have already fired and been auto-removed
@@ -123,9 +210,11 @@ pub fn create_emoji_grid(search_entry: &gtk4::SearchEntry) -> Box {
             }));
         }
         
-        let query = entry.text().to_string().to_lowercase();
+        let query = entry.text().to_string();
         let current_query_clone = current_query.clone();
         let filter_weak = filter.downgrade();
+        let sorter_weak = sorter.downgrade();
+        let selection_weak = selection_model.downgrade();
         let debounce_source_clone = debounce_source.clone();
         
         // Start new debounce timer
@@ -134,10 +223,18 @@ pub fn create_emoji_grid(search_entry: &gtk4::SearchEntry) -> Box {
             move || {
                 // Clear the source reference since timer fired (source auto-removed)
                 *debounce_source_clone.borrow_mut() = None;
-                *current_query_clone.borrow_mut() = query;
+                *current_query_clone.borrow_mut() = Query::new(&query, ItemKind::Emoji);
+                // filter first, it scores the items the sorter compares
                 if let Some(f) = filter_weak.upgrade() {
                     f.changed(gtk4::FilterChange::Different);
                 }
+                if let Some(s) = sorter_weak.upgrade() {
+                    s.changed(gtk4::SorterChange::Different);
+                }
+                // best match first, for Enter
+                if let Some(sel) = selection_weak.upgrade() {
+                    sel.set_selected(0);
+                }
             }
         );
         *debounce_source.borrow_mut() = Some(source_id);
@@ -145,20 +242,24 @@ pub fn create_emoji_grid(search_entry: &gtk4::SearchEntry) -> Box {
 
     // 3. Category Buttons (Sidebar)
     let categories = [
-        ("🕙", EmojiCategory::Recent),
-        ("🙂", EmojiCategory::SmileysAndPeople),
-        ("🐻", EmojiCategory::AnimalsAndNature),
-        ("🍔", EmojiCategory::FoodAndDrink),
-        ("⚽", EmojiCategory::Activities),
-        ("✈️", EmojiCategory::TravelAndPlaces),
-        ("💡", EmojiCategory::Objects),
-        ("🔣", EmojiCategory::Symbols),
-        ("🚩", EmojiCategory::Flags),
+        ("🕙", EmojiCategory::Recent, "recent"),
+        ("⭐", EmojiCategory::Favorites, "favorites"),
+        ("🙂", EmojiCategory::SmileysAndPeople, "smileys"),
+        ("🐻", EmojiCategory::AnimalsAndNature, "animals"),
+        ("🍔", EmojiCategory::FoodAndDrink, "food"),
+        ("⚽", EmojiCategory::Activities, "activities"),
+        ("✈️", EmojiCategory::TravelAndPlaces, "travel"),
+        ("💡", EmojiCategory::Objects, "objects"),
+        ("🔣", EmojiCategory::Symbols, "symbols"),
+        ("🚩", EmojiCategory::Flags, "flags"),
     ];
 
+    let mut category_buttons = sidebar::Categories::default();
     let mut first_btn = None;
+    let mut recent_btn = None;
+    let mut default_btn = None;
 
-    for (icon, cat_val) in categories {
+    for (icon, cat_val, id) in categories {
         let btn = ToggleButton::builder()
             .label(icon)
             .css_classes(["category-btn", "flat"])
@@ -172,7 +273,10 @@ pub fn create_emoji_grid(search_entry: &gtk4::SearchEntry) -> Box {
 
         if cat_val == EmojiCategory::SmileysAndPeople {
              btn.set_active(true);
-        } 
+             default_btn = Some(btn.clone());
+        } else if cat_val == EmojiCategory::Recent {
+             recent_btn = Some(btn.clone());
+        }
 
         btn.connect_toggled(glib::clone!(#[strong] current_category, #[weak] filter, move |b| {
             if b.is_active() {
@@ -181,66 +285,79 @@ pub fn create_emoji_grid(search_entry: &gtk4::SearchEntry) -> Box {
             }
         }));
         
+        category_buttons.add(id, &btn);
         sidebar.append(&btn);
     }
     
+    if let (Some(recent), Some(fallback)) = (&recent_btn, &default_btn) {
+        sidebar::bind_recent_visibility(recent, fallback);
+    }
+    if let Some(default) = &default_btn {
+        category_buttons.set_default(default);
+    }
     container.append(&sidebar);
 
     // 4. Factory & Grid
     let factory = SignalListItemFactory::new();
     factory.connect_setup(move |_factory, item| {
+         let item = item.downcast_ref::<gtk4::ListItem>().unwrap();
          let button = gtk4::Button::builder().css_classes(["emoji-btn", "flat"]).build();
          item.set_child(Some(&button));
          
          // Left Click (Primary)
          button.connect_clicked(move |btn| {
              let text = btn.label().unwrap_or_default().to_string();
-             insert_helper(text);
+             compose::pick(ItemKind::Emoji, text);
          });
 
-         // Right Click (Secondary) - Skin Tones
+         // Right Click (Secondary) - Skin Tones & Favorites
          let gesture = gtk4::GestureClick::new();
          gesture.set_button(3); // Right click
          
          let button_weak = button.downgrade();
-         gesture.connect_pressed(move |_gesture, _, _, _| {
+         gesture.connect_pressed(glib::clone!(#[weak] item, move |_gesture, _, _, _| {
              let btn = match button_weak.upgrade() {
                  Some(b) => b,
                  None => return,
              };
-             let base_emoji = btn.label().unwrap_or_default().to_string();
+             // the cell's item, the label may show one of its variants
+             let Some(entry) = item.item().and_downcast::<EmojiObject>() else { return };
+             let base_emoji = emoji_variants::base_of(&entry.emoji());
+             let (popover, container) = item_menu::new_item_popover(&btn);
              
-             if let Some(emoji_data) = emojis::get(&base_emoji) {
-                 if let Some(variants) = emoji_data.skin_tones() {
-                     // Create Popover
-                     let popover = Popover::builder().child(&Box::new(Orientation::Horizontal, 5)).build();
-                     let container = popover.child().unwrap().downcast::<Box>().unwrap();
-                     container.set_margin_top(5);
-                     container.set_margin_bottom(5);
-                     container.set_margin_start(5);
-                     container.set_margin_end(5);
-
-                     // Add variants
-                     for variant in variants {
-                         let v_btn = gtk4::Button::builder()
-                            .label(variant.as_str())
-                            .css_classes(["emoji-btn-small", "flat"])
-                            .build();
-                         
-                         let v_text = variant.as_str().to_string();
-                         let pop_clone = popover.clone();
-                         v_btn.connect_clicked(move |_| {
-                             insert_helper(v_text.clone());
-                             pop_clone.popdown();
-                         });
-                         container.append(&v_btn);
-                     }
+             // a picked variant is shown & inserted by default from now on
+             let pick: Rc<dyn Fn(String)> = Rc::new(glib::clone!(#[strong] base_emoji, #[weak] btn, #[weak] popover, move |variant: String| {
+                 crate::history::remember_variant(&base_emoji, &variant);
+                 btn.set_label(&variant);
+                 compose::pick(ItemKind::Emoji, variant);
+                 popover.popdown();
+             }));
+
+             let base = emojis::get(&base_emoji);
+             for row in base.map(emoji_variants::variant_rows).unwrap_or_default() {
+                 let tones = Box::new(Orientation::Horizontal, 5);
+
+                 // Add variants
+                 for variant in row {
+                     let v_btn = gtk4::Button::builder()
+                        .label(variant.as_str())
+                        .css_classes(["emoji-btn-small", "flat"])
+                        .build();
                      
-                     popover.set_parent(&btn);
-                     popover.popup();
+                     let v_text = variant.as_str().to_string();
+                     let pick = pick.clone();
+                     v_btn.connect_clicked(move |_| pick(v_text.clone()));
+                     tones.append(&v_btn);
                  }
+                 container.append(&tones);
              }
-         });
+             if let Some(base) = base.filter(|e| emoji_variants::is_multi_person(e)) {
+                 container.append(&pair_tone_picker(base, pick));
+             }
+             
+             container.append(&item_menu::favorite_toggle_button(&base_emoji, &popover));
+             popover.popup();
+         }));
          button.add_controller(gesture);
     });
 
@@ -248,7 +365,7 @@ pub fn create_emoji_grid(search_entry: &gtk4::SearchEntry) -> Box {
         let item = item.downcast_ref::<gtk4::ListItem>().unwrap();
         let button = item.child().unwrap().downcast::<gtk4::Button>().unwrap();
         let entry = item.item().unwrap().downcast::<EmojiObject>().unwrap();
-        button.set_label(&entry.emoji());
+        button.set_label(&display_text(&entry));
         button.set_tooltip_text(Some(&entry.name()));
     });
 
@@ -259,6 +376,13 @@ pub fn create_emoji_grid(search_entry: &gtk4::SearchEntry) -> Box {
         .min_columns(5)
         .build();
 
+    // Enter on the keyboard-selected item
+    grid_view.connect_activate(|view, position| {
+        if let Some(obj) = view.model().and_then(|m| m.item(position)).and_downcast::<EmojiObject>() {
+            compose::pick(ItemKind::Emoji, display_text(&obj));
+        }
+    });
+
     let scrolled_window = ScrolledWindow::builder()
         .hscrollbar_policy(PolicyType::Never)
         .vscrollbar_policy(PolicyType::Automatic)
@@ -268,5 +392,5 @@ pub fn create_emoji_grid(search_entry: &gtk4::SearchEntry) -> Box {
         .build();
 
     container.append(&scrolled_window);
-    container
+    (container, page::Page::for_grid(category_buttons, &grid_view))
 }
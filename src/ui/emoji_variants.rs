@@ -0,0 +1,156 @@
+use emojis::Emoji;
+use crate::settings::{Gender, Hair, Settings, SkinTone};
+use super::emoji_data;
+
+const PERSON: char = '\u{1F9D1}';
+const WOMAN: char = '\u{1F469}';
+const MAN: char = '\u{1F468}';
+const ZWJ: char = '\u{200D}';
+
+/// Modifier shown for each per-person tone, lightest first
+pub const TONE_MODIFIERS: [&str; 5] = ["\u{1F3FB}", "\u{1F3FC}", "\u{1F3FD}", "\u{1F3FE}", "\u{1F3FF}"];
+
+// tone of a two-person emoji by the first (row) and second (column) person's tone
+const PAIR_TONES: [[emojis::SkinTone; 5]; 5] = {
+    use emojis::SkinTone::*;
+    [
+        [Light, LightAndMediumLight, LightAndMedium, LightAndMediumDark, LightAndDark],
+        [MediumLightAndLight, MediumLight, MediumLightAndMedium, MediumLightAndMediumDark, MediumLightAndDark],
+        [MediumAndLight, MediumAndMediumLight, Medium, MediumAndMediumDark, MediumAndDark],
+        [MediumDarkAndLight, MediumDarkAndMediumLight, MediumDarkAndMedium, MediumDark, MediumDarkAndDark],
+        [DarkAndLight, DarkAndMediumLight, DarkAndMedium, DarkAndMediumDark, Dark],
+    ]
+};
+
+/// Menu entries for the variant preferences: action target, label, value
+pub const SKIN_TONES: &[(&str, &str, SkinTone)] = &[
+    ("default", "Default", SkinTone::Default),
+    ("light", "Light", SkinTone::Light),
+    ("medium-light", "Medium-Light", SkinTone::MediumLight),
+    ("medium", "Medium", SkinTone::Medium),
+    ("medium-dark", "Medium-Dark", SkinTone::MediumDark),
+    ("dark", "Dark", SkinTone::Dark),
+];
+
+pub const GENDERS: &[(&str, &str, Gender)] = &[
+    ("neutral", "Neutral", Gender::Neutral),
+    ("woman", "Woman", Gender::Woman),
+    ("man", "Man", Gender::Man),
+];
+
+pub const HAIR_STYLES: &[(&str, &str, Hair)] = &[
+    ("default", "Default", Hair::Default),
+    ("red", "Red", Hair::Red),
+    ("curly", "Curly", Hair::Curly),
+    ("white", "White", Hair::White),
+    ("bald", "Bald", Hair::Bald),
+];
+
+/// Same emoji for another gender, 🧑‍💻 -> 👩‍💻 or 🏃 -> 🏃‍♀️.
+/// None if Unicode has no such variant.
+pub fn with_gender(emoji: &'static Emoji, gender: Gender) -> Option<&'static Emoji> {
+    let (person, sign) = match gender {
+        Gender::Neutral => return Some(emoji),
+        Gender::Woman => (WOMAN, '\u{2640}'),
+        Gender::Man => (MAN, '\u{2642}'),
+    };
+    // sequences built on 🧑 swap it, the rest take a ♀️/♂️ suffix
+    match emoji.as_str().strip_prefix(PERSON) {
+        Some(rest) => emojis::get(&format!("{}{}", person, rest)),
+        None => emojis::get(&format!("{}{}{}\u{FE0F}", emoji, ZWJ, sign)),
+    }
+}
+
+// 🧑 -> 🧑‍🦰, only person, woman & man have hair styles
+fn with_hair(emoji: &'static Emoji, hair: Hair) -> Option<&'static Emoji> {
+    let component = match hair {
+        Hair::Default => return Some(emoji),
+        Hair::Red => '\u{1F9B0}',
+        Hair::Curly => '\u{1F9B1}',
+        Hair::Bald => '\u{1F9B2}',
+        Hair::White => '\u{1F9B3}',
+    };
+    emojis::get(&format!("{}{}{}", emoji, ZWJ, component))
+}
+
+fn skin_tone(tone: SkinTone) -> emojis::SkinTone {
+    match tone {
+        SkinTone::Default => emojis::SkinTone::Default,
+        SkinTone::Light => emojis::SkinTone::Light,
+        SkinTone::MediumLight => emojis::SkinTone::MediumLight,
+        SkinTone::Medium => emojis::SkinTone::Medium,
+        SkinTone::MediumDark => emojis::SkinTone::MediumDark,
+        SkinTone::Dark => emojis::SkinTone::Dark,
+    }
+}
+
+/// `emoji` with the preferred gender, hair style and skin tone, each one
+/// applied only where Unicode has that variant
+pub fn apply_preferences(emoji: &'static Emoji, settings: &Settings) -> &'static Emoji {
+    let available = emoji_data::availability();
+    let emoji = with_gender(emoji, settings.gender).filter(|e| available(e)).unwrap_or(emoji);
+    let emoji = with_hair(emoji, settings.hair).filter(|e| available(e)).unwrap_or(emoji);
+    emoji.with_skin_tone(skin_tone(settings.skin_tone)).filter(|e| available(e)).unwrap_or(emoji)
+}
+
+/// What a grid cell for `base` shows and inserts: the variant last picked
+/// from its menu, otherwise `base` with the preferred variants
+pub fn preferred(base: &str) -> String {
+    if let Some(variant) = crate::history::get_variant(base) {
+        return variant;
+    }
+    match emojis::get(base) {
+        Some(emoji) => apply_preferences(emoji, &crate::settings::get()).to_string(),
+        None => base.to_string(),
+    }
+}
+
+/// Key variant choices are remembered under: the emoji without skin tone
+pub fn base_of(text: &str) -> String {
+    match emojis::get(text) {
+        Some(emoji) => emoji.with_skin_tone(emojis::SkinTone::Default).unwrap_or(emoji).to_string(),
+        None => text.to_string(),
+    }
+}
+
+// one tone for the whole emoji, unlike the per-person ones of 🤝 & co.
+fn is_uniform(emoji: &Emoji) -> bool {
+    use emojis::SkinTone::*;
+    matches!(emoji.skin_tone(), None | Some(Default | Light | MediumLight | Medium | MediumDark | Dark))
+}
+
+/// Whether both people (or hands) of `emoji` can have their own tone, 🤝 or 💑
+pub fn is_multi_person(emoji: &'static Emoji) -> bool {
+    emoji.with_skin_tone(emojis::SkinTone::LightAndDark).is_some()
+}
+
+/// `emoji` with its first and second person in the `first` and `second` tone
+/// (indices into `TONE_MODIFIERS`). None if Unicode has no such sequence.
+pub fn with_pair_tones(emoji: &'static Emoji, first: usize, second: usize) -> Option<&'static Emoji> {
+    let tone = *PAIR_TONES.get(first)?.get(second)?;
+    emoji.with_skin_tone(tone).filter(|e| e.skin_tone() == Some(tone) && emoji_data::availability()(e))
+}
+
+/// Index into `TONE_MODIFIERS` of the preferred skin tone, None for the default yellow
+pub fn preferred_tone_index() -> Option<usize> {
+    let tone = skin_tone(crate::settings::get().skin_tone);
+    PAIR_TONES.iter().enumerate().position(|(i, row)| row[i] == tone)
+}
+
+/// Rows for an emoji's variant menu, one per gender Unicode has,
+/// each with its skin tones. Empty if there is nothing to pick.
+pub fn variant_rows(emoji: &'static Emoji) -> Vec<Vec<&'static Emoji>> {
+    let available = emoji_data::availability();
+    let rows: Vec<Vec<&'static Emoji>> = GENDERS.iter()
+        .filter_map(|&(_, _, gender)| with_gender(emoji, gender))
+        .filter(|e| available(e))
+        .map(|e| match e.skin_tones() {
+            Some(tones) => tones.filter(|e| is_uniform(e) && available(e)).collect(),
+            None => vec![e],
+        })
+        .collect();
+    if rows.iter().all(|row| row.len() < 2) && rows.len() < 2 {
+        return Vec::new();
+    }
+    rows
+}
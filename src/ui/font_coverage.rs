@@ -0,0 +1,77 @@
+use gtk4::pango;
+use gtk4::pango::prelude::*;
+use gtk4::prelude::*;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+/// Which characters the fonts GTK draws text with have glyphs for
+struct FontCoverage {
+    fontset: pango::Fontset,
+    cache: HashMap<char, bool>,
+    // shapes ZWJ sequences
+    layout: pango::Layout,
+    sequences: HashMap<String, bool>,
+}
+
+impl FontCoverage {
+    fn new() -> Option<Self> {
+        let context = gtk4::Label::new(None).pango_context();
+        let description = context.font_description()?;
+        let fontset = context.load_fontset(&description, &pango::Language::default())?;
+        let layout = pango::Layout::new(&context);
+        Some(Self { fontset, cache: HashMap::new(), layout, sequences: HashMap::new() })
+    }
+
+    fn has_glyph(&mut self, c: char) -> bool {
+        let fontset = &self.fontset;
+        // the fontset falls back to its first font when none covers `c`
+        *self.cache.entry(c).or_insert_with(|| fontset.font(c as u32).has_char(c))
+    }
+
+    // A ZWJ sequence the font knows becomes a single glyph, newer ones built
+    // from older characters (🐦‍🔥) fall apart into one glyph per part
+    fn has_ligature(&mut self, text: &str) -> bool {
+        let layout = &self.layout;
+        *self.sequences.entry(text.to_string()).or_insert_with(|| {
+            layout.set_text(text);
+            let glyphs: i32 = layout.line_readonly(0)
+                .map(|line| line.runs().iter().map(|run| run.glyph_string().num_glyphs()).sum())
+                .unwrap_or(0);
+            glyphs == 1
+        })
+    }
+}
+
+thread_local! {
+    // loaded on first use, GTK has to be up by then
+    static COVERAGE: RefCell<Option<FontCoverage>> = const { RefCell::new(None) };
+}
+
+const ZWJ: char = '\u{200D}';
+
+// joiners, variation selectors & tags shape other characters and have no glyph of their own
+fn is_invisible(c: char) -> bool {
+    matches!(c, '\u{200D}' | '\u{FE0E}' | '\u{FE0F}' | '\u{E0020}'..='\u{E007F}')
+}
+
+/// Whether every character of `text` has a glyph in the installed fonts and
+/// a ZWJ sequence is drawn as one emoji rather than its parts.
+/// Without a display (command line) everything counts as renderable.
+pub fn can_render(text: &str) -> bool {
+    if !gtk4::is_initialized_main_thread() {
+        return true;
+    }
+    COVERAGE.with(|coverage| {
+        let mut coverage = coverage.borrow_mut();
+        if coverage.is_none() {
+            *coverage = FontCoverage::new();
+        }
+        match coverage.as_mut() {
+            Some(coverage) => {
+                text.chars().filter(|&c| !is_invisible(c)).all(|c| coverage.has_glyph(c))
+                    && (!text.contains(ZWJ) || coverage.has_ligature(text))
+            }
+            None => true,
+        }
+    })
+}
@@ -1,21 +1,33 @@
 use gtk4::prelude::*;
 use gtk4::{
     gio, glib, GridView, SignalListItemFactory, SingleSelection,
-    PolicyType, ScrolledWindow, Box, Orientation, Spinner
+    PolicyType, ScrolledWindow, Box, Orientation, Spinner, ToggleButton
 };
 use super::gif_data::{GifObject, GifData, search_gifs, get_trending_gifs};
-use crate::dbus::DBusClient;
-use std::cell::RefCell;
+use super::{page, sidebar};
+use std::cell::{Cell, RefCell};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
 use std::rc::Rc;
 
 // helper function: copy URL and insert via extension
-fn insert_gif_url(url: String) {
+pub fn insert_gif(gif: &GifObject) {
     crate::app::mark_inserting();
-    DBusClient::insert_or_copy(&url);
+    crate::history::add_recent_gif(gif.full_url(), gif.preview_url());
+    crate::insertion::insert_or_copy(&gif.full_url());
+}
+
+// fill store with recently used GIFs, entries saved before previews were
+// recorded get a placeholder rather than the full-size GIF
+fn load_recent_gifs(store: &gio::ListStore) {
+    store.remove_all();
+    for (url, preview) in crate::history::get_recent_gifs() {
+        store.append(&GifObject::new(url.clone(), String::new(), preview.unwrap_or_default(), url));
+    }
 }
 
 // helper to run async code on tokio runtime and return result to GTK main loop
-fn spawn_tokio<F, T>(future: F, callback: impl FnOnce(T) + 'static)
+pub fn spawn_tokio<F, T>(future: F, callback: impl FnOnce(T) + 'static)
 where
     F: std::future::Future<Output = T> + Send + 'static,
     T: Send + 'static,
@@ -47,7 +59,7 @@ where
     });
 }
 
-pub fn create_gif_grid(search_entry: &gtk4::SearchEntry) -> Box {
+pub fn create_gif_grid(search_entry: &gtk4::SearchEntry) -> (Box, page::Page) {
     let container = Box::new(Orientation::Vertical, 0);
     container.set_css_classes(&["gif-page"]);
 
@@ -61,6 +73,29 @@ pub fn create_gif_grid(search_entry: &gtk4::SearchEntry) -> Box {
         .visible(false)  // shown only during loading
         .build();
     
+    // Trending / Recent switch, used while the search is empty
+    let mode_bar = Box::new(Orientation::Horizontal, 0);
+    mode_bar.set_css_classes(&["linked"]);
+    mode_bar.set_halign(gtk4::Align::Center);
+    mode_bar.set_margin_bottom(6);
+
+    let trending_btn = ToggleButton::builder()
+        .label("Trending")
+        .active(true)
+        .build();
+    let recent_btn = ToggleButton::builder()
+        .label("Recent")
+        .group(&trending_btn)
+        .build();
+    mode_bar.append(&trending_btn);
+    mode_bar.append(&recent_btn);
+    sidebar::bind_recent_visibility(&recent_btn, &trending_btn);
+    let mut category_buttons = sidebar::Categories::default();
+    category_buttons.add("trending", &trending_btn);
+    category_buttons.add("recent", &recent_btn);
+    category_buttons.set_default(&trending_btn);
+
+    container.append(&mode_bar);
     container.append(&spinner);
 
     let store = gio::ListStore::new::<GifObject>();
@@ -68,6 +103,7 @@ pub fn create_gif_grid(search_entry: &gtk4::SearchEntry) -> Box {
     let factory = SignalListItemFactory::new();
     
     factory.connect_setup(move |_factory, item| {
+        let item = item.downcast_ref::<gtk4::ListItem>().unwrap();
         let button = gtk4::Button::builder()
             .css_classes(["gif-btn", "flat"])
             .build();
@@ -80,14 +116,12 @@ pub fn create_gif_grid(search_entry: &gtk4::SearchEntry) -> Box {
         button.set_child(Some(&picture));
         item.set_child(Some(&button));
 
-        // click handler - copy URL
-        button.connect_clicked(move |btn| {
-            // url from widget name
-            let url = btn.widget_name();
-            if !url.is_empty() {
-                insert_gif_url(url.to_string());
+        // click handler - copy URL of the bound GIF
+        button.connect_clicked(glib::clone!(#[weak] item, move |_| {
+            if let Some(gif) = item.item().and_downcast::<GifObject>() {
+                insert_gif(&gif);
             }
-        });
+        }));
     });
 
     factory.connect_bind(move |_factory, item| {
@@ -104,60 +138,19 @@ pub fn create_gif_grid(search_entry: &gtk4::SearchEntry) -> Box {
         button.set_widget_name(&full_url);
 
         // load GIF asynchronously
-        let picture_weak = picture.downgrade();
-        let full_url_check = full_url.clone();
-        
-        spawn_tokio(
-            async move {
-                let client = reqwest::Client::new();
-                match client.get(&preview_url).send().await {
-                    Ok(response) => response.bytes().await.ok().map(|b| (b, gif_id)),
-                    Err(e) => {
-                        eprintln!("Failed to fetch GIF: {}", e);
-                        None
-                    }
-                }
-            },
-            move |result_opt| {
-                if let Some((bytes, id)) = result_opt {
-                    if let Some(pic) = picture_weak.upgrade() {
-                        if let Some(parent) = pic.parent() {
-                             if let Ok(btn) = parent.downcast::<gtk4::Button>() {
-                                 if btn.widget_name() != full_url_check {
-                                     // widget reused for another item, discard result
-                                     return;
-                                 }
-                                 
-                                 // check if widget is still in the component tree
-                                 if pic.root().is_none() {
-                                     return;
-                                 }
-                             } else {
-                                 return;
-                             }
-                        } else {
-                            return;
-                        }
-
-                        let temp_dir = std::env::temp_dir();
-                        let temp_path = temp_dir.join(format!("carmenta_gif_{}.gif", id));
-                        
-                        if std::fs::write(&temp_path, &bytes).is_ok() {
-                            let file = gio::File::for_path(&temp_path);
-                            let media = gtk4::MediaFile::for_file(&file);
-                            media.set_loop(true);
-                            media.play();
-                            pic.set_paintable(Some(&media));
-                        }
-                    }
-                }
+        load_preview(&picture, preview_url, gif_id, move |pic| {
+            match pic.parent().and_then(|p| p.downcast::<gtk4::Button>().ok()) {
+                // widget may have been reused for another item meanwhile
+                Some(btn) => btn.widget_name() == full_url,
+                None => false,
             }
-        );
+        });
     });
 
     // cleanup MediaFile when item is unbound
     // without it there is loud and sexy segfault
     factory.connect_unbind(move |_factory, item| {
+        let item = item.downcast_ref::<gtk4::ListItem>().unwrap();
         if let Some(button) = item.child() {
             if let Ok(button) = button.downcast::<gtk4::Button>() {
                 button.set_widget_name("");
@@ -185,6 +178,13 @@ pub fn create_gif_grid(search_entry: &gtk4::SearchEntry) -> Box {
         .min_columns(3)
         .build();
 
+    // Enter on the keyboard-selected GIF
+    grid_view.connect_activate(|view, position| {
+        if let Some(gif) = view.model().and_then(|m| m.item(position)).and_downcast::<GifObject>() {
+            insert_gif(&gif);
+        }
+    });
+
     let scrolled_window = ScrolledWindow::builder()
         .hscrollbar_policy(PolicyType::Never)
         .vscrollbar_policy(PolicyType::Automatic)
@@ -199,11 +199,13 @@ pub fn create_gif_grid(search_entry: &gtk4::SearchEntry) -> Box {
     let debounce_source: Rc<RefCell<Option<glib::SourceId>>> = Rc::new(RefCell::new(None));
     let store_weak = store.downgrade();
     let spinner_weak = spinner.downgrade();
+    let show_recent = Rc::new(Cell::new(false));
     
     search_entry.connect_search_changed(glib::clone!(
         #[strong] debounce_source,
         #[strong] store_weak,
         #[strong] spinner_weak,
+        #[strong] show_recent,
         move |entry| {
             // cancel previous debounce timer
             if let Some(source_id) = debounce_source.borrow_mut().take() {
@@ -213,6 +215,13 @@ pub fn create_gif_grid(search_entry: &gtk4::SearchEntry) -> Box {
             }
 
             let query = entry.text().to_string();
+            if query.is_empty() && show_recent.get() {
+                if let Some(store) = store_weak.upgrade() {
+                    load_recent_gifs(&store);
+                }
+                return;
+            }
+
             let store_weak_clone = store_weak.clone();
             let spinner_weak_clone = spinner_weak.clone();
             let debounce_source_clone = debounce_source.clone();
@@ -267,9 +276,107 @@ pub fn create_gif_grid(search_entry: &gtk4::SearchEntry) -> Box {
         }
     ));
 
+    // switch between Recent and Trending
+    recent_btn.connect_toggled(glib::clone!(
+        #[strong] show_recent,
+        #[weak] store,
+        #[weak] spinner,
+        #[weak] search_entry,
+        move |b| {
+            show_recent.set(b.is_active());
+            if !search_entry.text().is_empty() {
+                return; // search results stay until the query is cleared
+            }
+            if b.is_active() {
+                load_recent_gifs(&store);
+            } else {
+                load_trending_gifs(&store, &spinner);
+            }
+        }
+    ));
+
+    // keep Recent view in sync with history
+    let store_weak = store.downgrade();
+    let search_weak = search_entry.downgrade();
+    crate::history::on_history_changed(move || {
+        if !show_recent.get() {
+            return;
+        }
+        if let (Some(store), Some(entry)) = (store_weak.upgrade(), search_weak.upgrade()) {
+            if entry.text().is_empty() {
+                load_recent_gifs(&store);
+            }
+        }
+    });
+
     // load trending GIFs on startup
+    load_trending_gifs(&store, &spinner);
+
+    (container, page::Page::for_grid(category_buttons, &grid_view))
+}
+
+/// Download a GIF preview and play it in `picture`, unless `is_current`
+/// reports the picture got reused for another GIF meanwhile
+pub fn load_preview(
+    picture: &gtk4::Picture,
+    preview_url: String,
+    gif_id: String,
+    is_current: impl Fn(&gtk4::Picture) -> bool + 'static,
+) {
+    if preview_url.is_empty() {
+        let icon = gtk4::IconTheme::for_display(&picture.display()).lookup_icon(
+            "image-x-generic-symbolic",
+            &[],
+            48,
+            1,
+            gtk4::TextDirection::None,
+            gtk4::IconLookupFlags::empty(),
+        );
+        picture.set_paintable(Some(&icon));
+        return;
+    }
+
+    let picture_weak = picture.downgrade();
+    spawn_tokio(
+        async move {
+            let client = reqwest::Client::new();
+            match client.get(&preview_url).send().await {
+                Ok(response) => response.bytes().await.ok().map(|b| (b, gif_id)),
+                Err(e) => {
+                    eprintln!("Failed to fetch GIF: {}", e);
+                    None
+                }
+            }
+        },
+        move |result_opt| {
+            let Some((bytes, id)) = result_opt else { return };
+            let Some(pic) = picture_weak.upgrade() else { return };
+            // check if widget is still in the component tree
+            if !is_current(&pic) || pic.root().is_none() {
+                return;
+            }
+
+            let temp_dir = std::env::temp_dir();
+            // ids of recent GIFs are URLs, hash them into a file name
+            let mut hasher = DefaultHasher::new();
+            id.hash(&mut hasher);
+            let temp_path = temp_dir.join(format!("carmenta_gif_{:016x}.gif", hasher.finish()));
+
+            if std::fs::write(&temp_path, &bytes).is_ok() {
+                let file = gio::File::for_path(&temp_path);
+                let media = gtk4::MediaFile::for_file(&file);
+                media.set_loop(true);
+                media.play();
+                pic.set_paintable(Some(&media));
+            }
+        }
+    );
+}
+
+fn load_trending_gifs(store: &gio::ListStore, spinner: &Spinner) {
     let store_init = store.clone();
     let spinner_init = spinner.clone();
+    store_init.remove_all();
     spinner_init.set_visible(true);
     spinner_init.set_spinning(true);
     
@@ -292,7 +399,4 @@ pub fn create_gif_grid(search_entry: &gtk4::SearchEntry) -> Box {
             }
         }
     );
-
-    container
 }
-
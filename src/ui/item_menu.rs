@@ -0,0 +1,65 @@
+use gtk4::prelude::*;
+use gtk4::{glib, Box, Button, GestureClick, Orientation, Popover};
+
+/// Popover anchored to a grid item, unparented again once closed
+pub fn new_item_popover(parent: &Button) -> (Popover, Box) {
+    let container = Box::new(Orientation::Vertical, 5);
+    container.set_margin_top(5);
+    container.set_margin_bottom(5);
+    container.set_margin_start(5);
+    container.set_margin_end(5);
+
+    let popover = Popover::builder().child(&container).build();
+    popover.set_parent(parent);
+    popover.connect_closed(|p| {
+        // unparenting inside the closed handler confuses GTK, defer it
+        let p = p.clone();
+        glib::idle_add_local_once(move || p.unparent());
+    });
+
+    (popover, container)
+}
+
+/// "Add to / Remove from Favorites" entry for an item context menu
+pub fn favorite_toggle_button(text: &str, popover: &Popover) -> Button {
+    let label = if crate::favorites::is_favorite(text) {
+        "Remove from Favorites"
+    } else {
+        "Add to Favorites"
+    };
+
+    let button = Button::builder()
+        .label(label)
+        .css_classes(["flat"])
+        .build();
+
+    let text = text.to_string();
+    let popover_weak = popover.downgrade();
+    button.connect_clicked(move |_| {
+        crate::favorites::toggle_favorite(text.clone());
+        if let Some(p) = popover_weak.upgrade() {
+            p.popdown();
+        }
+    });
+    button
+}
+
+/// Right-click menu for grid items without extra options (kaomoji, symbols)
+pub fn attach_context_menu(button: &Button) {
+    let gesture = GestureClick::new();
+    gesture.set_button(3); // Right click
+
+    let button_weak = button.downgrade();
+    gesture.connect_pressed(move |_gesture, _, _, _| {
+        let btn = match button_weak.upgrade() {
+            Some(b) => b,
+            None => return,
+        };
+        let text = btn.label().unwrap_or_default().to_string();
+
+        let (popover, container) = new_item_popover(&btn);
+        container.append(&favorite_toggle_button(&text, &popover));
+        popover.popup();
+    });
+    button.add_controller(gesture);
+}
@@ -1,6 +1,7 @@
 use gtk4::glib;
 use gtk4::subclass::prelude::*;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use crate::search::Query;
 
 // --- GObject Definition ---
 
@@ -13,6 +14,7 @@ mod imp {
         pub name: RefCell<String>,
         pub category: RefCell<KaomojiCategory>,
         pub keywords: RefCell<Vec<String>>,
+        pub search_score: Cell<u32>, // set by the search filter
     }
 
     #[glib::object_subclass]
@@ -50,6 +52,29 @@ impl KaomojiObject {
     pub fn category(&self) -> KaomojiCategory {
         *self.imp().category.borrow()
     }
+
+    pub fn keywords(&self) -> Vec<String> {
+        self.imp().keywords.borrow().clone()
+    }
+
+    /// Score against a search query, kept for sorting. Returns whether it matched.
+    pub fn rank(&self, query: &Query) -> bool {
+        let text = self.text();
+        let mut terms = self.keywords();
+        terms.push(text.to_lowercase());
+        let score = query.score(&text, &terms);
+        self.imp().search_score.set(score.unwrap_or(0));
+        score.is_some()
+    }
+
+    pub fn search_score(&self) -> u32 {
+        self.imp().search_score.get()
+    }
+
+    /// Copy of this kaomoji filed under another category (Recent)
+    pub fn with_category(&self, category: KaomojiCategory) -> Self {
+        Self::new(self.text(), self.name(), category, self.keywords())
+    }
 }
 
 // --- Data & Categories ---
@@ -63,6 +88,8 @@ pub enum KaomojiCategory {
     Anger,
     Sorrow,
     Actions,
+    Recent, // Special
+    Favorites, // Special
 }
 
 pub fn get_all_kaomojis() -> Vec<KaomojiObject> {
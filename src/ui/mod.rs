@@ -1,8 +1,17 @@
+pub mod all_results;
+pub mod compose;
+pub mod diagnostics;
+pub mod emoji_annotations;
 pub mod emoji_data;
 pub mod emoji_grid;
+pub mod emoji_variants;
+pub mod font_coverage;
 pub mod kaomoji_data;
 pub mod kaomoji_grid;
 pub mod symbols_data;
 pub mod symbols_grid;
 pub mod gif_data;
 pub mod gif_grid;
+pub mod item_menu;
+pub mod page;
+pub mod sidebar;
@@ -0,0 +1,77 @@
+use gtk4::prelude::*;
+use gtk4::{gio, glib, GridView, SingleSelection};
+use std::collections::HashMap;
+use std::rc::Rc;
+use crate::history::ItemKind;
+use super::sidebar::Categories;
+
+/// What the window needs from a ViewStack page for launch options and keyboard control
+#[derive(Clone)]
+pub struct Page {
+    pub categories: Categories,
+    // receives focus on Down/Tab from the search entry
+    results: gtk4::Widget,
+    // Enter in the search entry, inserts the current item
+    activate: Rc<dyn Fn()>,
+}
+
+impl Page {
+    pub fn new(categories: Categories, results: &impl IsA<gtk4::Widget>, activate: impl Fn() + 'static) -> Self {
+        Self {
+            categories,
+            results: results.clone().upcast(),
+            activate: Rc::new(activate),
+        }
+    }
+
+    /// Page around a grid whose `activate` signal inserts an item
+    pub fn for_grid(categories: Categories, grid: &GridView) -> Self {
+        let grid_weak = grid.downgrade();
+        Self::new(categories, grid, move || {
+            let Some(grid) = grid_weak.upgrade() else { return };
+            let Some(selection) = grid.model().and_downcast::<SingleSelection>() else { return };
+            let position = selection.selected();
+            if position != gtk4::INVALID_LIST_POSITION {
+                grid.emit_by_name::<()>("activate", &[&position]);
+            }
+        })
+    }
+
+    /// Move keyboard focus into the results, false if there is nothing to focus
+    pub fn focus_results(&self) -> bool {
+        self.results.child_focus(gtk4::DirectionType::TabForward) || self.results.grab_focus()
+    }
+
+    pub fn activate(&self) {
+        (self.activate)();
+    }
+}
+
+/// Keep `kind`'s history (best frecency first) at the start of `store`: copies
+/// of the `by_text` items made by `as_recent`, recognised again by `is_recent`
+pub fn bind_recent<T: IsA<glib::Object>>(
+    store: &gio::ListStore,
+    kind: ItemKind,
+    by_text: HashMap<String, T>,
+    is_recent: fn(&T) -> bool,
+    as_recent: fn(&T) -> T,
+) {
+    let rebuild = move |store: &gio::ListStore| {
+        while store.item(0).and_downcast::<T>().is_some_and(|item| is_recent(&item)) {
+            store.remove(0);
+        }
+        let items: Vec<T> = crate::history::get_recent(kind).iter()
+            .filter_map(|text| by_text.get(text))
+            .map(as_recent)
+            .collect();
+        store.splice(0, 0, &items);
+    };
+    rebuild(store);
+
+    let store_weak = store.downgrade();
+    crate::history::on_history_changed(move || {
+        if let Some(store) = store_weak.upgrade() {
+            rebuild(&store);
+        }
+    });
+}
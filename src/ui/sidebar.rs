@@ -0,0 +1,62 @@
+use gtk4::prelude::*;
+use gtk4::{glib, ToggleButton};
+
+/// Hide a page's Recent button while incognito, moving the selection to
+/// `fallback` if Recent was the active category
+pub fn bind_recent_visibility(recent: &ToggleButton, fallback: &ToggleButton) {
+    let update = glib::clone!(#[weak] recent, #[weak] fallback, move || {
+        let incognito = crate::history::is_incognito();
+        if incognito && recent.is_active() {
+            fallback.set_active(true);
+        }
+        recent.set_visible(!incognito);
+    });
+    update();
+    crate::history::on_history_changed(update);
+}
+
+/// A page's category buttons, addressable by id for launch options
+#[derive(Clone, Default)]
+pub struct Categories {
+    buttons: Vec<(&'static str, ToggleButton)>,
+    default: Option<ToggleButton>,
+}
+
+impl Categories {
+    pub fn add(&mut self, id: &'static str, button: &ToggleButton) {
+        self.buttons.push((id, button.clone()));
+    }
+
+    /// Category selected when the page opens
+    pub fn set_default(&mut self, button: &ToggleButton) {
+        self.default = Some(button.clone());
+    }
+
+    pub fn reset(&self) {
+        if let Some(button) = &self.default {
+            button.set_active(true);
+        }
+    }
+
+    /// Activate the `n`th visible category (Alt+1..9), false if there are fewer
+    pub fn select_nth(&self, n: usize) -> bool {
+        match self.buttons.iter().map(|(_, b)| b).filter(|b| b.is_visible()).nth(n) {
+            Some(button) => {
+                button.set_active(true);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Activate category `id`, false if the page has no such (visible) category
+    pub fn select(&self, id: &str) -> bool {
+        match self.buttons.iter().find(|(i, b)| *i == id && b.is_visible()) {
+            Some((_, button)) => {
+                button.set_active(true);
+                true
+            }
+            None => false,
+        }
+    }
+}
@@ -1,6 +1,7 @@
 use gtk4::glib;
 use gtk4::subclass::prelude::*;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use crate::search::Query;
 use unicode_blocks as ub;
 
 // --- GObject Definition ---
@@ -13,6 +14,7 @@ mod imp {
         pub char: RefCell<String>,
         pub name: RefCell<String>, // block name
         pub category: RefCell<SymbolCategory>,
+        pub search_score: Cell<u32>, // set by the search filter
     }
 
     #[glib::object_subclass]
@@ -49,6 +51,26 @@ impl SymbolObject {
     pub fn category(&self) -> SymbolCategory {
         *self.imp().category.borrow()
     }
+
+    /// Score against a search query, kept for sorting. Returns whether it matched.
+    pub fn rank(&self, query: &Query) -> bool {
+        let score = query.score(&self.char(), &[self.name().to_lowercase()]);
+        self.imp().search_score.set(score.unwrap_or(0));
+        score.is_some()
+    }
+
+    pub fn search_score(&self) -> u32 {
+        self.imp().search_score.get()
+    }
+
+    /// Copy of this symbol filed under another category (Recent)
+    pub fn with_category(&self, category: SymbolCategory) -> Self {
+        let obj: Self = glib::Object::builder().build();
+        *obj.imp().char.borrow_mut() = self.char();
+        *obj.imp().name.borrow_mut() = self.name();
+        *obj.imp().category.borrow_mut() = category;
+        obj
+    }
 }
 
 // --- Data & Categories ---
@@ -62,6 +84,8 @@ pub enum SymbolCategory {
     Tech,
     BoxDrawing,
     Misc,
+    Recent, // Special
+    Favorites, // Special
 }
 
 pub fn get_symbols() -> Vec<SymbolObject> {
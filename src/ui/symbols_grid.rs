@@ -2,14 +2,17 @@ use gtk4::prelude::*;
 use gtk4::{
     gio, glib, GridView, SignalListItemFactory, SingleSelection, 
     PolicyType, ScrolledWindow, Box, Orientation, ToggleButton, 
-    CustomFilter, FilterListModel
+    CustomFilter, FilterListModel, SortListModel
 };
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::rc::Rc;
-use crate::dbus::DBusClient;
+use crate::history::ItemKind;
+use crate::search::{self, Query};
 use super::symbols_data::{SymbolObject, SymbolCategory, get_symbols};
+use super::{compose, item_menu, page, sidebar};
 
-pub fn create_symbols_grid(search_entry: &gtk4::SearchEntry) -> Box {
+pub fn create_symbols_grid(search_entry: &gtk4::SearchEntry) -> (Box, page::Page) {
     let container = Box::new(Orientation::Horizontal, 0);
     container.set_css_classes(&["emoji-page"]);
 
@@ -22,29 +25,54 @@ pub fn create_symbols_grid(search_entry: &gtk4::SearchEntry) -> Box {
 
     // 2. Store
     let store = gio::ListStore::new::<SymbolObject>();
-    store.extend_from_slice(&get_symbols());
+    let all_symbols = get_symbols();
+    store.extend_from_slice(&all_symbols);
+
+    // lookup for building Recent items from history
+    let by_text: HashMap<String, SymbolObject> = all_symbols.into_iter().map(|s| (s.char(), s)).collect();
+
+    page::bind_recent(
+        &store,
+        ItemKind::Symbol,
+        by_text,
+        |item| item.category() == SymbolCategory::Recent,
+        |item| item.with_category(SymbolCategory::Recent),
+    );
 
     // 3. Filter
     let current_category = Rc::new(RefCell::new(SymbolCategory::Arrows));
-    let current_query = Rc::new(RefCell::new(String::new()));
+    let current_query = Rc::new(RefCell::new(Query::default()));
 
     let filter = CustomFilter::new(glib::clone!(#[strong] current_category, #[strong] current_query, move |obj| {
         let sym = obj.downcast_ref::<SymbolObject>().unwrap();
         let query = current_query.borrow();
 
         if !query.is_empty() {
-            return sym.name().to_lowercase().contains(query.as_str());
+            // skip Recent during search to avoid duplicates
+            if sym.category() == SymbolCategory::Recent {
+                return false;
+            }
+            return sym.rank(&query);
         }
         
-        sym.category() == *current_category.borrow()
+        let category = *current_category.borrow();
+        if category == SymbolCategory::Favorites {
+            return sym.category() != SymbolCategory::Recent && crate::favorites::is_favorite(&sym.char());
+        }
+
+        sym.category() == category
     }));
 
+    // best matches first while searching
+    let sorter = search::score_sorter(current_query.clone(), SymbolObject::search_score);
+
     let filter_model = FilterListModel::new(Some(store), Some(filter.clone()));
-    let selection_model = SingleSelection::new(Some(filter_model));
+    let sort_model = SortListModel::new(Some(filter_model), Some(sorter.clone()));
+    let selection_model = SingleSelection::new(Some(sort_model));
 
     // Connect Search with debounce (150ms)
     let debounce_source: Rc<RefCell<Option<glib::SourceId>>> = Rc::new(RefCell::new(None));
-    search_entry.connect_search_changed(glib::clone!(#[weak] filter, #[strong] current_query, #[strong] debounce_source, move |entry| {
+    search_entry.connect_search_changed(glib::clone!(#[weak] filter, #[weak] sorter, #[weak] selection_model, #[strong] current_query, #[strong] debounce_source, move |entry| {
         // Cancel previous debounce timer if still pending
         if let Some(source_id) = debounce_source.borrow_mut().take() {
             let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
@@ -52,19 +80,29 @@ pub fn create_symbols_grid(search_entry: &gtk4::SearchEntry) -> Box {
             }));
         }
         
-        let query = entry.text().to_string().to_lowercase();
+        let query = entry.text().to_string();
         let current_query_clone = current_query.clone();
         let filter_weak = filter.downgrade();
+        let sorter_weak = sorter.downgrade();
+        let selection_weak = selection_model.downgrade();
         let debounce_source_clone = debounce_source.clone();
         
         let source_id = glib::timeout_add_local_once(
             std::time::Duration::from_millis(150),
             move || {
                 *debounce_source_clone.borrow_mut() = None;
-                *current_query_clone.borrow_mut() = query;
+                *current_query_clone.borrow_mut() = Query::new(&query, ItemKind::Symbol);
+                // filter first, it scores the items the sorter compares
                 if let Some(f) = filter_weak.upgrade() {
                     f.changed(gtk4::FilterChange::Different);
                 }
+                if let Some(s) = sorter_weak.upgrade() {
+                    s.changed(gtk4::SorterChange::Different);
+                }
+                // best match first, for Enter
+                if let Some(sel) = selection_weak.upgrade() {
+                    sel.set_selected(0);
+                }
             }
         );
         *debounce_source.borrow_mut() = Some(source_id);
@@ -72,16 +110,21 @@ pub fn create_symbols_grid(search_entry: &gtk4::SearchEntry) -> Box {
 
     // Buttons
     let categories = vec![
-        ("→", SymbolCategory::Arrows, "Arrows"),
-        ("∑", SymbolCategory::Math, "Math"),
-        ("€", SymbolCategory::Currency, "Currency"),
-        ("⌘", SymbolCategory::Tech, "Technical"),
-        ("┏", SymbolCategory::BoxDrawing, "Box Drawing"),
-        ("★", SymbolCategory::Misc, "Misc"),
+        ("🕙", SymbolCategory::Recent, "Recent", "recent"),
+        ("⭐", SymbolCategory::Favorites, "Favorites", "favorites"),
+        ("→", SymbolCategory::Arrows, "Arrows", "arrows"),
+        ("∑", SymbolCategory::Math, "Math", "math"),
+        ("€", SymbolCategory::Currency, "Currency", "currency"),
+        ("⌘", SymbolCategory::Tech, "Technical", "tech"),
+        ("┏", SymbolCategory::BoxDrawing, "Box Drawing", "box-drawing"),
+        ("★", SymbolCategory::Misc, "Misc", "misc"),
     ];
 
+    let mut category_buttons = sidebar::Categories::default();
     let mut first_btn: Option<ToggleButton> = None;
-    for (icon, cat, tooltip) in categories {
+    let mut recent_btn = None;
+    let mut default_btn = None;
+    for (icon, cat, tooltip, id) in categories {
         let btn = ToggleButton::builder()
             .label(icon)
             .tooltip_text(tooltip)
@@ -92,7 +135,13 @@ pub fn create_symbols_grid(search_entry: &gtk4::SearchEntry) -> Box {
             btn.set_group(Some(first));
         } else {
             first_btn = Some(btn.clone());
+        }
+
+        if cat == SymbolCategory::Arrows {
             btn.set_active(true);
+            default_btn = Some(btn.clone());
+        } else if cat == SymbolCategory::Recent {
+            recent_btn = Some(btn.clone());
         }
 
         let cat_val = cat;
@@ -102,24 +151,35 @@ pub fn create_symbols_grid(search_entry: &gtk4::SearchEntry) -> Box {
                 filter.changed(gtk4::FilterChange::Different);
             }
         }));
+        category_buttons.add(id, &btn);
         sidebar.append(&btn);
     }
+    if let (Some(recent), Some(fallback)) = (&recent_btn, &default_btn) {
+        sidebar::bind_recent_visibility(recent, fallback);
+    }
+    if let Some(default) = &default_btn {
+        category_buttons.set_default(default);
+    }
     container.append(&sidebar);
 
+    // Refresh Favorites view when favorites change
+    let filter_weak = filter.downgrade();
+    crate::favorites::on_favorites_changed(move || {
+        if let Some(f) = filter_weak.upgrade() {
+            f.changed(gtk4::FilterChange::Different);
+        }
+    });
+
     // 4. Grid Factory
     let factory = SignalListItemFactory::new();
     factory.connect_setup(move |_factory, item| {
          let item = item.downcast_ref::<gtk4::ListItem>().unwrap();
          let button = gtk4::Button::builder().css_classes(["emoji-btn", "flat"]).build();
          item.set_child(Some(&button));
+         item_menu::attach_context_menu(&button);
          button.connect_clicked(move |btn| {
              let text = btn.label().unwrap_or_default().to_string();
-             
-             // History + Insertion Logic
-             crate::app::mark_inserting();
-             crate::history::add_recent(text.clone());
-             
-             DBusClient::insert_or_copy(&text);
+             compose::pick(ItemKind::Symbol, text);
          });
     });
 
@@ -138,6 +198,13 @@ pub fn create_symbols_grid(search_entry: &gtk4::SearchEntry) -> Box {
         .enable_rubberband(false)
         .build();
 
+    // Enter on the keyboard-selected item
+    grid_view.connect_activate(|view, position| {
+        if let Some(obj) = view.model().and_then(|m| m.item(position)).and_downcast::<SymbolObject>() {
+            compose::pick(ItemKind::Symbol, obj.char());
+        }
+    });
+
     let scrolled = ScrolledWindow::builder()
         .child(&grid_view)
         .hscrollbar_policy(PolicyType::Never)
@@ -147,5 +214,5 @@ pub fn create_symbols_grid(search_entry: &gtk4::SearchEntry) -> Box {
 
     container.append(&scrolled);
     
-    container
+    (container, page::Page::for_grid(category_buttons, &grid_view))
 }
@@ -1,10 +1,24 @@
 use gtk4::prelude::*;
+use libadwaita::prelude::*;
 use libadwaita::{Application, ApplicationWindow};
 use gtk4::{Box, Orientation, SearchEntry, gio};
 use gtk4::glib;
+use crate::app::LaunchOptions;
+use crate::settings::Settings;
+use crate::ui::emoji_variants;
+use crate::ui::page::Page;
+use std::cell::Cell;
+use std::rc::Rc;
 
 pub struct CarmentaWindow {
     pub window: ApplicationWindow,
+    stack: libadwaita::ViewStack,
+    search_entry: SearchEntry,
+    // ViewStack pages in switcher order, by page name
+    pages: Rc<Vec<(&'static str, Page)>>,
+    toasts: libadwaita::ToastOverlay,
+    // on the start page without the user picking one, typing searches everywhere
+    browsing: Rc<Cell<bool>>,
 }
 
 impl CarmentaWindow {
@@ -12,8 +26,41 @@ impl CarmentaWindow {
 
         // Menu
         let menu = gio::Menu::new();
-        menu.append(Some("About Carmenta"), Some("app.about"));
-        menu.append(Some("Quit"), Some("app.quit"));
+        let history_section = gio::Menu::new();
+        history_section.append(Some("Incognito"), Some("app.incognito"));
+        history_section.append(Some("Export History…"), Some("app.export-history"));
+        history_section.append(Some("Import History…"), Some("app.import-history"));
+        history_section.append(Some("Clear History…"), Some("app.clear-history"));
+        menu.append_section(None, &history_section);
+        let language_menu = gio::Menu::new();
+        language_menu.append(Some("Automatic"), Some("app.emoji-language::auto"));
+        for &(locale, label) in crate::ui::emoji_annotations::LANGUAGES {
+            language_menu.append(Some(label), Some(&format!("app.emoji-language::{}", locale)));
+        }
+        let settings_section = gio::Menu::new();
+        settings_section.append_submenu(Some("Emoji Language"), &language_menu);
+        settings_section.append_submenu(Some("Skin Tone"), &choice_menu("skin-tone", emoji_variants::SKIN_TONES));
+        settings_section.append_submenu(Some("Gender"), &choice_menu("gender", emoji_variants::GENDERS));
+        settings_section.append_submenu(Some("Hair Style"), &choice_menu("hair", emoji_variants::HAIR_STYLES));
+        let version_menu = gio::Menu::new();
+        version_menu.append(Some("All"), Some("app.max-emoji-version::all"));
+        for version in crate::ui::emoji_data::EMOJI_VERSIONS {
+            version_menu.append(Some(&format!("Emoji {} and Older", version)), Some(&format!("app.max-emoji-version::{}", version)));
+        }
+        settings_section.append_submenu(Some("Newest Emoji"), &version_menu);
+        let backend_menu = gio::Menu::new();
+        backend_menu.append(Some("Automatic"), Some("app.insertion-backend::auto"));
+        for backend in crate::insertion::backends().iter() {
+            backend_menu.append(Some(backend.name()), Some(&format!("app.insertion-backend::{}", backend.id())));
+        }
+        settings_section.append_submenu(Some("Insertion Method"), &backend_menu);
+        settings_section.append_submenu(Some("Restore Clipboard"), &choice_menu("clipboard-restore", crate::insertion::RESTORE_DELAYS));
+        menu.append_section(None, &settings_section);
+        let app_section = gio::Menu::new();
+        app_section.append(Some("Diagnostics"), Some("app.diagnostics"));
+        app_section.append(Some("About Carmenta"), Some("app.about"));
+        app_section.append(Some("Quit"), Some("app.quit"));
+        menu.append_section(None, &app_section);
 
         // Actions (App Scope)
         if !app.has_action("about") {
@@ -24,6 +71,17 @@ impl CarmentaWindow {
             app.add_action(&action_about);
         }
 
+        if !app.has_action("diagnostics") {
+            let action_diagnostics = gio::SimpleAction::new("diagnostics", None);
+            let app_weak = app.downgrade();
+            action_diagnostics.connect_activate(move |_, _| {
+                if let Some(win) = app_weak.upgrade().and_then(|a| a.active_window()) {
+                    crate::ui::diagnostics::show_diagnostics(&win);
+                }
+            });
+            app.add_action(&action_diagnostics);
+        }
+
         if !app.has_action("quit") {
             let action_quit = gio::SimpleAction::new("quit", None);
             let app_weak = app.downgrade();
@@ -35,6 +93,118 @@ impl CarmentaWindow {
             app.add_action(&action_quit);
         }
 
+        if !app.has_action("incognito") {
+            let action_incognito = gio::SimpleAction::new_stateful(
+                "incognito",
+                None,
+                &crate::history::is_incognito().to_variant(),
+            );
+            action_incognito.connect_change_state(|action, state| {
+                if let Some(incognito) = state.and_then(|s| s.get::<bool>()) {
+                    action.set_state(&incognito.to_variant());
+                    crate::history::set_incognito(incognito);
+                }
+            });
+            app.add_action(&action_incognito);
+        }
+
+        // "auto" follows the session locale
+        if !app.has_action("emoji-language") {
+            let current = crate::settings::get().locale.unwrap_or_else(|| "auto".to_string());
+            let action_language = gio::SimpleAction::new_stateful(
+                "emoji-language",
+                Some(glib::VariantTy::STRING),
+                &current.to_variant(),
+            );
+            action_language.connect_change_state(|action, state| {
+                if let Some(language) = state.and_then(|s| s.get::<String>()) {
+                    action.set_state(&language.to_variant());
+                    crate::settings::update(|s| {
+                        s.locale = (language != "auto").then_some(language);
+                    });
+                }
+            });
+            app.add_action(&action_language);
+        }
+
+        // "all" shows every emoji the fonts can draw
+        if !app.has_action("max-emoji-version") {
+            let current = crate::settings::get().max_emoji_version.unwrap_or_else(|| "all".to_string());
+            let action_version = gio::SimpleAction::new_stateful(
+                "max-emoji-version",
+                Some(glib::VariantTy::STRING),
+                &current.to_variant(),
+            );
+            action_version.connect_change_state(|action, state| {
+                if let Some(version) = state.and_then(|s| s.get::<String>()) {
+                    action.set_state(&version.to_variant());
+                    crate::settings::update(|s| {
+                        s.max_emoji_version = (version != "all").then_some(version);
+                    });
+                }
+            });
+            app.add_action(&action_version);
+        }
+
+        // "auto" picks the first backend that works in this session
+        if !app.has_action("insertion-backend") {
+            let current = crate::settings::get().insertion_backend.unwrap_or_else(|| "auto".to_string());
+            let action_backend = gio::SimpleAction::new_stateful(
+                "insertion-backend",
+                Some(glib::VariantTy::STRING),
+                &current.to_variant(),
+            );
+            action_backend.connect_change_state(|action, state| {
+                if let Some(backend) = state.and_then(|s| s.get::<String>()) {
+                    action.set_state(&backend.to_variant());
+                    crate::settings::update(|s| {
+                        s.insertion_backend = (backend != "auto").then_some(backend);
+                    });
+                }
+            });
+            app.add_action(&action_backend);
+        }
+
+        // Preferred emoji variants
+        let settings = crate::settings::get();
+        add_choice_action(app, "skin-tone", emoji_variants::SKIN_TONES, settings.skin_tone, |s, tone| s.skin_tone = tone);
+        add_choice_action(app, "gender", emoji_variants::GENDERS, settings.gender, |s, gender| s.gender = gender);
+        add_choice_action(app, "hair", emoji_variants::HAIR_STYLES, settings.hair, |s, hair| s.hair = hair);
+        add_choice_action(app, "clipboard-restore", crate::insertion::RESTORE_DELAYS, settings.clipboard_restore_delay, |s, delay| s.clipboard_restore_delay = delay);
+
+        if !app.has_action("clear-history") {
+            let action_clear = gio::SimpleAction::new("clear-history", None);
+            let app_weak = app.downgrade();
+            action_clear.connect_activate(move |_, _| {
+                if let Some(win) = app_weak.upgrade().and_then(|a| a.active_window()) {
+                    confirm_clear_history(&win);
+                }
+            });
+            app.add_action(&action_clear);
+        }
+
+        if !app.has_action("export-history") {
+            let action_export = gio::SimpleAction::new("export-history", None);
+            let app_weak = app.downgrade();
+            action_export.connect_activate(move |_, _| {
+                if let Some(win) = app_weak.upgrade().and_then(|a| a.active_window()) {
+                    export_history(&win);
+                }
+            });
+            app.add_action(&action_export);
+        }
+
+        if !app.has_action("import-history") {
+            let action_import = gio::SimpleAction::new("import-history", None);
+            let app_weak = app.downgrade();
+            action_import.connect_activate(move |_, _| {
+                if let Some(win) = app_weak.upgrade().and_then(|a| a.active_window()) {
+                    import_history(&win);
+                }
+            });
+            app.add_action(&action_import);
+        }
+
         // Top Bar Layout (Search + Menu)
         let top_bar = Box::new(Orientation::Horizontal, 6);
         top_bar.set_margin_top(12);
@@ -55,36 +225,137 @@ impl CarmentaWindow {
             .valign(gtk4::Align::Center)
             .build();
             
+        // Incognito indicator
+        let incognito_icon = gtk4::Image::builder()
+            .icon_name("view-conceal-symbolic")
+            .tooltip_text("Incognito: history is not recorded")
+            .valign(gtk4::Align::Center)
+            .visible(crate::history::is_incognito())
+            .build();
+        let icon_weak = incognito_icon.downgrade();
+        crate::history::on_history_changed(move || {
+            if let Some(icon) = icon_weak.upgrade() {
+                icon.set_visible(crate::history::is_incognito());
+            }
+        });
+            
+        // Extension indicator: shown when insertion falls back to the clipboard
+        let extension_icon = gtk4::Image::builder()
+            .icon_name("dialog-warning-symbolic")
+            .valign(gtk4::Align::Center)
+            .build();
+        let update_extension_icon = glib::clone!(#[weak] extension_icon, move || {
+            let problem = crate::dbus::extension_status().problem();
+            extension_icon.set_tooltip_text(problem.as_deref());
+            extension_icon.set_visible(problem.is_some());
+        });
+        update_extension_icon();
+        crate::dbus::on_status_changed(update_extension_icon);
+
+        // Compose mode: picks collect in a buffer that is inserted at once
+        let compose_toggle = gtk4::ToggleButton::builder()
+            .icon_name("document-edit-symbolic")
+            .tooltip_text("Compose: collect several items, then insert them at once (or Shift+click)")
+            .valign(gtk4::Align::Center)
+            .build();
+        let compose_bar = crate::ui::compose::create_compose_bar(&compose_toggle);
+
         top_bar.append(&search_entry);
+        top_bar.append(&compose_toggle);
+        top_bar.append(&incognito_icon);
+        top_bar.append(&extension_icon);
         top_bar.append(&menu_button);
 
         // Main Layout
         let content = Box::new(Orientation::Vertical, 0);
         content.append(&top_bar);
+        content.append(&compose_bar);
 
         // 2. View Stack (Tabs)
         let stack = libadwaita::ViewStack::new();
+        let mut pages = Vec::new();
+
+        // -- All Page (search everywhere) --
+        let (all_page, page_handle) = crate::ui::all_results::create_all_results(&search_entry);
+        pages.push(("all", page_handle));
+        let page = stack.add_titled(&all_page, Some("all"), "All");
+        page.set_icon_name(Some("edit-find-symbolic"));
         
         // -- Emoji Page --
-        let emoji_page = crate::ui::emoji_grid::create_emoji_grid(&search_entry);
+        let (emoji_page, page_handle) = crate::ui::emoji_grid::create_emoji_grid(&search_entry);
+        pages.push(("emoji", page_handle));
         let page = stack.add_titled(&emoji_page, Some("emoji"), "Emoji");
         page.set_icon_name(Some("face-smile-symbolic"));
 
         // -- Kaomoji Page --
-        let kaomoji_page = crate::ui::kaomoji_grid::create_kaomoji_grid(&search_entry);
+        let (kaomoji_page, page_handle) = crate::ui::kaomoji_grid::create_kaomoji_grid(&search_entry);
+        pages.push(("kaomoji", page_handle));
         let page = stack.add_titled(&kaomoji_page, Some("kaomoji"), "Kaomoji");
         page.set_icon_name(Some("face-wink-symbolic"));
 
         // -- Symbols Page --
-        let symbols_page = crate::ui::symbols_grid::create_symbols_grid(&search_entry);
+        let (symbols_page, page_handle) = crate::ui::symbols_grid::create_symbols_grid(&search_entry);
+        pages.push(("symbols", page_handle));
         let page = stack.add_titled(&symbols_page, Some("symbols"), "Symbols");
         page.set_icon_name(Some("preferences-desktop-font-symbolic"));
 
         // -- GIF Page --
-        let gif_page = crate::ui::gif_grid::create_gif_grid(&search_entry);
+        let (gif_page, page_handle) = crate::ui::gif_grid::create_gif_grid(&search_entry);
+        pages.push(("gifs", page_handle));
         let page = stack.add_titled(&gif_page, Some("gifs"), "GIFs");
         page.set_icon_name(Some("emblem-photos-symbolic"));
 
+        // All comes first in the switcher, but Emoji is where the picker opens
+        stack.set_visible_child_name(START_PAGE);
+        let pages = Rc::new(pages);
+
+        // Typing on the start page switches to All, clearing the search goes back.
+        // Picking a page by hand keeps it.
+        let browsing = Rc::new(Cell::new(true));
+        let switching = Rc::new(Cell::new(false));
+        search_entry.connect_search_changed(glib::clone!(#[weak] stack, #[strong] browsing, #[strong] switching, move |entry| {
+            if !browsing.get() {
+                return;
+            }
+            let page = if entry.text().is_empty() { START_PAGE } else { "all" };
+            if stack.visible_child_name().as_deref() != Some(page) {
+                switching.set(true);
+                stack.set_visible_child_name(page);
+                switching.set(false);
+            }
+        }));
+        stack.connect_visible_child_notify(glib::clone!(#[strong] browsing, #[strong] switching, move |_| {
+            if !switching.get() {
+                browsing.set(false);
+            }
+        }));
+
+        // Enter on a complete `:shortcode:` (optionally `::skin-tone-N:`) inserts it right away,
+        // otherwise it inserts the selected item of the visible page
+        search_entry.connect_activate(glib::clone!(#[weak] stack, #[strong] pages, move |entry| {
+            if let Some(emoji) = crate::shortcode::resolve(&entry.text()) {
+                crate::ui::compose::pick(crate::history::ItemKind::Emoji, emoji.as_str().to_string());
+            } else if let Some(page) = visible_page(&stack, &pages) {
+                page.activate();
+            }
+        }));
+
+        // Down/Tab leave the search entry for the results
+        let entry_keys = gtk4::EventControllerKey::new();
+        entry_keys.set_propagation_phase(gtk4::PropagationPhase::Capture);
+        entry_keys.connect_key_pressed(glib::clone!(#[weak] stack, #[strong] pages, #[upgrade_or] glib::Propagation::Proceed, move |_, key, _, modifiers| {
+            let plain = !modifiers.intersects(gtk4::gdk::ModifierType::CONTROL_MASK | gtk4::gdk::ModifierType::ALT_MASK | gtk4::gdk::ModifierType::SHIFT_MASK);
+            if plain && matches!(key, gtk4::gdk::Key::Down | gtk4::gdk::Key::Tab) {
+                if let Some(page) = visible_page(&stack, &pages) {
+                    if page.focus_results() {
+                        return glib::Propagation::Stop;
+                    }
+                }
+            }
+            glib::Propagation::Proceed
+        }));
+        search_entry.add_controller(entry_keys);
+
         // View Switcher (Bottom Bar)
         let view_switcher = libadwaita::ViewSwitcherBar::builder()
             .stack(&stack)
@@ -99,10 +370,14 @@ impl CarmentaWindow {
         main_box.append(&expanded_stack); // Content
         main_box.append(&view_switcher); // Tabs
 
+        // in-window notifications, e.g. which way an item got inserted
+        let toasts = libadwaita::ToastOverlay::new();
+        toasts.set_child(Some(&main_box));
+
         let window = ApplicationWindow::builder()
             .application(app)
             .title("Carmenta")
-            .content(&main_box)
+            .content(&toasts)
             .default_width(420)
             .default_height(480)
             .modal(false) // non-modal to interact with other apps
@@ -123,11 +398,10 @@ impl CarmentaWindow {
                 glib::timeout_add_local(std::time::Duration::from_millis(200), move || {
                     if let Some(w) = win_weak.upgrade() {
                          let is_inserting = crate::app::IS_INSERTING.with(|f| *f.borrow());
-                         if !w.is_active() && !is_inserting {
+                         let is_dialog_open = crate::app::IS_DIALOG_OPEN.with(|f| *f.borrow());
+                         if w.is_visible() && !w.is_active() && !is_inserting && !is_dialog_open {
                              println!("Focus lost confirmed -> Closing App");
-                             if let Some(app) = w.application() {
-                                 app.quit();
-                             }
+                             crate::app::dismiss();
                          }
                     }
                     glib::ControlFlow::Break
@@ -135,25 +409,229 @@ impl CarmentaWindow {
             }
         });
 
-        // Escape Key handler
+        // Window-wide keys: Escape, Ctrl+PageUp/PageDown for pages, Alt+1..9 for categories
         let key_controller = gtk4::EventControllerKey::new();
         key_controller.set_propagation_phase(gtk4::PropagationPhase::Capture);
-        let app_weak_key = app.downgrade();
-        key_controller.connect_key_pressed(move |_, key, _, _| {
-            if key == gtk4::gdk::Key::Escape {
-                if let Some(a) = app_weak_key.upgrade() {
-                    a.quit();
+        key_controller.connect_key_pressed(glib::clone!(#[weak] stack, #[strong] pages, #[upgrade_or] glib::Propagation::Proceed, move |controller, key, _, modifiers| {
+            use gtk4::gdk::{Key, ModifierType};
+
+            if key == Key::Escape {
+                // an open dialog (confirmation, diagnostics) closes on its own
+                let window = controller.widget().and_downcast::<ApplicationWindow>();
+                if window.is_some_and(|w| w.visible_dialog().is_some()) {
+                    return glib::Propagation::Proceed;
                 }
+                crate::app::dismiss();
                 return glib::Propagation::Stop;
             }
+
+            if modifiers.contains(ModifierType::CONTROL_MASK) && matches!(key, Key::Page_Up | Key::Page_Down) {
+                let current = pages.iter().position(|(name, _)| stack.visible_child_name().as_deref() == Some(*name));
+                let step = if key == Key::Page_Down { 1 } else { pages.len() - 1 };
+                let next = current.map_or(0, |i| (i + step) % pages.len());
+                stack.set_visible_child_name(pages[next].0);
+                return glib::Propagation::Stop;
+            }
+
+            if modifiers.contains(ModifierType::ALT_MASK) {
+                if let Some(digit @ 1..=9) = key.to_unicode().and_then(|c| c.to_digit(10)) {
+                    if let Some(page) = visible_page(&stack, &pages) {
+                        page.categories.select_nth(digit as usize - 1);
+                    }
+                    return glib::Propagation::Stop;
+                }
+            }
+            glib::Propagation::Proceed
+        }));
+        key_controller.connect_modifiers(|_, modifiers| {
+            crate::ui::compose::set_shift_held(modifiers.contains(gtk4::gdk::ModifierType::SHIFT_MASK));
             glib::Propagation::Proceed
         });
         window.add_controller(key_controller);
 
-        Self { window }
+        // resident mode keeps the window around for the next activation
+        window.connect_close_request(|win| {
+            if crate::app::is_resident() {
+                win.set_visible(false);
+                return glib::Propagation::Stop;
+            }
+            glib::Propagation::Proceed
+        });
+
+        Self { window, stack, search_entry, pages, toasts, browsing }
     }
 
     pub fn present(&self) {
         self.window.present();
     }
+
+    pub fn show_toast(&self, message: &str) {
+        let toast = libadwaita::Toast::new(message);
+        toast.set_timeout(3);
+        self.toasts.add_toast(toast);
+    }
+
+    /// Back to a fresh picker: empty search, default category on every page
+    pub fn reset(&self) {
+        if self.stack.visible_child_name().as_deref() == Some(START_PAGE) {
+            self.browsing.set(true);
+        }
+        self.search_entry.set_text("");
+        crate::ui::compose::clear();
+        for (_, page) in self.pages.iter() {
+            page.categories.reset();
+        }
+    }
+
+    /// Open on the page, category and query requested on the command line
+    pub fn apply_launch_options(&self, options: &LaunchOptions) {
+        if let Some(page) = &options.page {
+            if self.stack.child_by_name(page).is_some() {
+                self.stack.set_visible_child_name(page);
+            } else {
+                eprintln!("Unknown page '{}', expected one of: all, emoji, kaomoji, symbols, gifs", page);
+            }
+        }
+
+        if let Some(category) = &options.category {
+            self.browsing.set(false);
+            // look on the requested page only, otherwise on the first page that has it
+            let visible = self.stack.visible_child_name();
+            let found = self.pages.iter()
+                .filter(|(name, _)| options.page.is_none() || visible.as_deref() == Some(*name))
+                .find(|(_, page)| page.categories.select(category));
+            match found {
+                Some((name, _)) => self.stack.set_visible_child_name(name),
+                None => eprintln!("Unknown category '{}'", category),
+            }
+        }
+
+        if let Some(query) = &options.query {
+            self.search_entry.set_text(query);
+            self.search_entry.set_position(-1);
+        }
+    }
+}
+
+// where the picker opens
+const START_PAGE: &str = "emoji";
+
+fn visible_page<'a>(stack: &libadwaita::ViewStack, pages: &'a [(&'static str, Page)]) -> Option<&'a Page> {
+    let visible = stack.visible_child_name()?;
+    pages.iter().find(|(name, _)| visible == *name).map(|(_, page)| page)
+}
+
+// Radio submenu for `app.<action>` over (target, label, value) choices
+fn choice_menu<T>(action: &str, choices: &[(&str, &str, T)]) -> gio::Menu {
+    let menu = gio::Menu::new();
+    for (target, label, _) in choices {
+        menu.append(Some(label), Some(&format!("app.{}::{}", action, target)));
+    }
+    menu
+}
+
+// Stateful string action picking one of `choices`, stored with `set`
+fn add_choice_action<T: Copy + PartialEq + 'static>(
+    app: &Application,
+    name: &str,
+    choices: &'static [(&'static str, &'static str, T)],
+    current: T,
+    set: fn(&mut Settings, T),
+) {
+    if app.has_action(name) {
+        return;
+    }
+    let current = choices.iter().find(|(_, _, v)| *v == current).map_or(choices[0].0, |c| c.0);
+    let action = gio::SimpleAction::new_stateful(name, Some(glib::VariantTy::STRING), &current.to_variant());
+    action.connect_change_state(move |action, state| {
+        let Some(target) = state.and_then(|s| s.get::<String>()) else { return };
+        if let Some(&(_, _, value)) = choices.iter().find(|(t, _, _)| *t == target) {
+            action.set_state(&target.to_variant());
+            crate::settings::update(|s| set(s, value));
+        }
+    });
+    app.add_action(&action);
+}
+
+fn show_error(parent: &gtk4::Window, heading: &str, body: &str) {
+    let dialog = libadwaita::AlertDialog::new(Some(heading), Some(body));
+    dialog.add_response("close", "_Close");
+    dialog.present(Some(parent));
+}
+
+fn json_file_dialog(title: &str) -> gtk4::FileDialog {
+    let filter = gtk4::FileFilter::new();
+    filter.set_name(Some("JSON"));
+    filter.add_mime_type("application/json");
+    filter.add_suffix("json");
+    let filters = gio::ListStore::new::<gtk4::FileFilter>();
+    filters.append(&filter);
+
+    gtk4::FileDialog::builder()
+        .title(title)
+        .modal(true)
+        .filters(&filters)
+        .build()
+}
+
+fn confirm_clear_history(parent: &gtk4::Window) {
+    let dialog = libadwaita::AlertDialog::new(
+        Some("Clear History?"),
+        Some("Recently used items of every page will be forgotten. Favorites are kept."),
+    );
+    dialog.add_responses(&[("cancel", "_Cancel"), ("clear", "C_lear")]);
+    dialog.set_response_appearance("clear", libadwaita::ResponseAppearance::Destructive);
+    dialog.set_default_response(Some("cancel"));
+    dialog.set_close_response("cancel");
+    dialog.connect_response(None, |_, response| {
+        if response == "clear" {
+            crate::history::clear();
+        }
+    });
+    dialog.present(Some(parent));
+}
+
+fn export_history(parent: &gtk4::Window) {
+    let dialog = json_file_dialog("Export History");
+    dialog.set_initial_name(Some("carmenta-history.json"));
+
+    crate::app::set_dialog_open(true);
+    let parent_weak = parent.downgrade();
+    dialog.save(Some(parent), gio::Cancellable::NONE, move |result| {
+        crate::app::set_dialog_open(false);
+        let Ok(file) = result else { return }; // dismissed
+
+        let written = crate::history::export_json().and_then(|json| {
+            file.replace_contents(&json, None, false, gio::FileCreateFlags::REPLACE_DESTINATION, gio::Cancellable::NONE)?;
+            Ok(())
+        });
+        if let Err(e) = written {
+            eprintln!("Failed to export history: {}", e);
+            if let Some(parent) = parent_weak.upgrade() {
+                show_error(&parent, "Export Failed", &e.to_string());
+            }
+        }
+    });
+}
+
+fn import_history(parent: &gtk4::Window) {
+    let dialog = json_file_dialog("Import History");
+
+    crate::app::set_dialog_open(true);
+    let parent_weak = parent.downgrade();
+    dialog.open(Some(parent), gio::Cancellable::NONE, move |result| {
+        crate::app::set_dialog_open(false);
+        let Ok(file) = result else { return }; // dismissed
+
+        let imported = file
+            .load_contents(gio::Cancellable::NONE)
+            .map_err(anyhow::Error::from)
+            .and_then(|(json, _)| crate::history::import_json(&json));
+        if let Err(e) = imported {
+            eprintln!("Failed to import history: {}", e);
+            if let Some(parent) = parent_weak.upgrade() {
+                show_error(&parent, "Import Failed", &format!("The file is not a valid Carmenta history: {}", e));
+            }
+        }
+    });
 }